@@ -0,0 +1,355 @@
+//Centrality measures for finding influential reviewers and games that bridge communities
+//Every measure gives one score per vertex, ranked separately for reviewers and games
+//since the two sides of the bipartite graph are not comparable
+use crate::graph::{bfs_visit, source_totals, Graph, NodeId, NodeKind, UNREACHABLE};
+use clap::ValueEnum;
+use rand::prelude::*;
+use rayon::prelude::*;
+use serde::Serialize;
+
+//Iterative measures stop once the scores move less than this in total, or after MAX_ITERATIONS
+const TOLERANCE: f64 = 1e-10;
+const MAX_ITERATIONS: usize = 1000;
+
+//Betweenness sources are split into this many chunks whatever the number of threads,
+//so the partial sums and the order they are added in never change between runs
+const BETWEENNESS_CHUNKS: usize = 64;
+
+//Which centrality is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Centrality {
+    Degree,
+    Closeness,
+    Betweenness,
+    PageRank,
+    Hits,
+}
+
+//Score of every vertex under one measure, indexed by NodeId
+#[derive(Debug, Clone, Serialize)]
+pub struct CentralityScores {
+    pub measure: Centrality,
+    pub scores: Vec<f64>,
+}
+
+impl CentralityScores {
+    pub fn score(&self, u: NodeId) -> f64 {
+        self.scores[u as usize]
+    }
+
+    //Vertices of one kind with their scores, highest first, ties broken by NodeId
+    pub fn ranked(&self, graph: &Graph, kind: NodeKind) -> Vec<(NodeId, f64)> {
+        let mut ranked: Vec<(NodeId, f64)> = graph.nodes_of(kind).map(|u| (u, self.score(u))).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+//Rescales the scores of one kind so they sum to 1, returning how far they moved
+fn normalize_side(graph: &Graph, kind: NodeKind, scores: &mut [f64], previous: &[f64]) -> f64 {
+    let total: f64 = graph.nodes_of(kind).map(|u| scores[u as usize]).sum();
+    let mut change = 0.0;
+    for u in graph.nodes_of(kind) {
+        if total > 0.0 {
+            scores[u as usize] /= total;
+        }
+        change += (scores[u as usize] - previous[u as usize]).abs();
+    }
+    change
+}
+
+impl Graph {
+    //Degree over the number of vertices on the other side, the most a vertex could have
+    //A reviewer who reviewed every game scores 1
+    pub fn degree_centrality(&self) -> Vec<f64> {
+        self.nodes()
+            .map(|u| {
+                let possible = match self.kind(u) {
+                    NodeKind::Reviewer => self.product_count(),
+                    NodeKind::Product => self.reviewer_count(),
+                };
+                if possible == 0 {
+                    0.0
+                } else {
+                    self.degree(u) as f64 / possible as f64
+                }
+            })
+            .collect()
+    }
+
+    //Closeness from one BFS per vertex, reusing the distance totals of the path averages
+    //Uses the Wasserman-Faust form (r / (n - 1)) * (r / total distance), where r is the number
+    //of vertices reached, so vertices in small components do not score as central
+    pub fn closeness_centrality(&self) -> Vec<f64> {
+        let n = self.node_count();
+        let all: Vec<NodeId> = self.nodes().collect();
+        source_totals(self, &all)
+            .iter()
+            .map(|t| {
+                if t.length == 0 {
+                    0.0
+                } else {
+                    let reached = t.reached as f64;
+                    (reached / (n - 1) as f64) * (reached / t.length as f64)
+                }
+            })
+            .collect()
+    }
+
+    //Brandes betweenness: the number of shortest paths between other pairs passing through each vertex
+    //With sources set, only that many random sources are searched and the totals are scaled
+    //up by n / sources, an unbiased estimate of the exact value
+    pub fn betweenness_centrality<R: Rng>(&self, sources: Option<usize>, rng: &mut R) -> Vec<f64> {
+        let n = self.node_count();
+        let all: Vec<NodeId> = self.nodes().collect();
+        let chosen: Vec<NodeId> = match sources {
+            Some(k) if k < n => all.choose_multiple(rng, k).copied().collect(),
+            _ => all,
+        };
+        //Floating point addition is not associative, so each chunk sums its sources in order
+        //and the chunks are added in index order to give bit-identical scores on every run
+        let size = chosen.len().div_ceil(BETWEENNESS_CHUNKS).max(1);
+        let partials: Vec<Vec<f64>> = chosen
+            .par_chunks(size)
+            .map(|chunk| {
+                let mut state = Brandes::new(n);
+                chunk.iter().for_each(|&source| state.accumulate(self, source));
+                state.betweenness
+            })
+            .collect();
+        let mut betweenness = vec![0.0; n];
+        for partial in partials {
+            betweenness.iter_mut().zip(partial).for_each(|(x, y)| *x += y);
+        }
+
+        //Every unordered pair is seen from both ends when all vertices are sources
+        let scale = if chosen.is_empty() { 0.0 } else { n as f64 / chosen.len() as f64 / 2.0 };
+        betweenness.iter_mut().for_each(|b| *b *= scale);
+        betweenness
+    }
+
+    //PageRank with the given damping factor, scores sum to 1
+    //Isolated vertices have no edges to follow, their rank is spread evenly over every vertex
+    pub fn pagerank(&self, damping: f64) -> Vec<f64> {
+        let n = self.node_count();
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..MAX_ITERATIONS {
+            let dangling: f64 = self.nodes().filter(|&u| self.degree(u) == 0).map(|u| rank[u as usize]).sum();
+            let base = (1.0 - damping + damping * dangling) / n as f64;
+            let next: Vec<f64> = self
+                .nodes()
+                .map(|u| {
+                    base + damping
+                        * self
+                            .neighbors(u)
+                            .iter()
+                            .map(|&v| rank[v as usize] / self.degree(v) as f64)
+                            .sum::<f64>()
+                })
+                .collect();
+            let change: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        rank
+    }
+
+    //HITS with reviews as links from reviewers to games
+    //Reviewers get hub scores and games authority scores: a good hub reviews authoritative
+    //games and an authoritative game is reviewed by good hubs. Each side sums to 1
+    pub fn hits(&self) -> Vec<f64> {
+        let n = self.node_count();
+        let mut scores = vec![1.0; n];
+        normalize_side(self, NodeKind::Reviewer, &mut scores, &vec![0.0; n]);
+        for _ in 0..MAX_ITERATIONS {
+            let previous = scores.clone();
+            let mut change = 0.0;
+            for kind in [NodeKind::Product, NodeKind::Reviewer] {
+                for u in self.nodes_of(kind) {
+                    scores[u as usize] = self.neighbors(u).iter().map(|&v| scores[v as usize]).sum();
+                }
+                change += normalize_side(self, kind, &mut scores, &previous);
+            }
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        scores
+    }
+
+    //Computes one centrality measure
+    //sources and rng are only used by betweenness, damping only by PageRank
+    pub fn centrality<R: Rng>(
+        &self,
+        measure: Centrality,
+        sources: Option<usize>,
+        damping: f64,
+        rng: &mut R,
+    ) -> CentralityScores {
+        let scores = match measure {
+            Centrality::Degree => self.degree_centrality(),
+            Centrality::Closeness => self.closeness_centrality(),
+            Centrality::Betweenness => self.betweenness_centrality(sources, rng),
+            Centrality::PageRank => self.pagerank(damping),
+            Centrality::Hits => self.hits(),
+        };
+        CentralityScores { measure, scores }
+    }
+}
+
+//Scratch space of one thread running Brandes' single-source accumulation
+//Only the vertices reached from a source are reset, so a state is reused for many sources
+struct Brandes {
+    distances: Vec<u32>,
+    paths: Vec<f64>,
+    dependency: Vec<f64>,
+    order: Vec<NodeId>,
+    betweenness: Vec<f64>,
+}
+
+impl Brandes {
+    fn new(n: usize) -> Brandes {
+        Brandes {
+            distances: vec![UNREACHABLE; n],
+            paths: vec![0.0; n],
+            dependency: vec![0.0; n],
+            order: Vec::new(),
+            betweenness: vec![0.0; n],
+        }
+    }
+
+    //Counts shortest paths from source with a BFS, then adds the dependencies in reverse BFS order
+    //The predecessors of w are the neighbours one level closer to the source, so no lists are kept
+    fn accumulate(&mut self, graph: &Graph, source: NodeId) {
+        bfs_visit(graph, source, &mut self.distances, &mut self.order);
+        self.paths[source as usize] = 1.0;
+        for &u in &self.order {
+            let next = self.distances[u as usize] + 1;
+            for &v in graph.neighbors(u) {
+                if self.distances[v as usize] == next {
+                    self.paths[v as usize] += self.paths[u as usize];
+                }
+            }
+        }
+        for &w in self.order.iter().rev() {
+            let depth = self.distances[w as usize];
+            let share = (1.0 + self.dependency[w as usize]) / self.paths[w as usize];
+            for &v in graph.neighbors(w) {
+                if depth > 0 && self.distances[v as usize] == depth - 1 {
+                    self.dependency[v as usize] += self.paths[v as usize] * share;
+                }
+            }
+            if w != source {
+                self.betweenness[w as usize] += self.dependency[w as usize];
+            }
+        }
+        for &u in &self.order {
+            self.distances[u as usize] = UNREACHABLE;
+            self.paths[u as usize] = 0.0;
+            self.dependency[u as usize] = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::two_game_graph;
+    use rand::rngs::StdRng;
+
+    //Reviewers A, B share game G1, reviewers C, D share game G3, and B and C both reviewed G2
+    //G2 is the only bridge between the two groups
+    fn bridge_graph() -> Graph {
+        let edges = vec![
+            ("A".to_string(), "G1".to_string()),
+            ("B".to_string(), "G1".to_string()),
+            ("B".to_string(), "G2".to_string()),
+            ("C".to_string(), "G2".to_string()),
+            ("C".to_string(), "G3".to_string()),
+            ("D".to_string(), "G3".to_string()),
+        ];
+        Graph::create_undirected(&edges)
+    }
+
+    #[test]
+    fn test_betweenness_of_path() {
+        //The graph is the path A - G1 - B - G2 - C - G3 - D
+        let graph = bridge_graph();
+        let mut rng = StdRng::seed_from_u64(1);
+        let scores = graph.centrality(Centrality::Betweenness, None, 0.85, &mut rng);
+        let id = |kind, name| graph.node_id(kind, name).unwrap();
+        assert_eq!(scores.score(id(NodeKind::Reviewer, "A")), 0.0);
+        assert_eq!(scores.score(id(NodeKind::Product, "G1")), 5.0);
+        assert_eq!(scores.score(id(NodeKind::Reviewer, "B")), 8.0);
+        assert_eq!(scores.score(id(NodeKind::Product, "G2")), 9.0);
+
+        let games = scores.ranked(&graph, NodeKind::Product);
+        assert_eq!(graph.name(games[0].0), "G2");
+        assert_eq!(games.len(), 3);
+    }
+
+    #[test]
+    fn test_betweenness_counts_split_paths() {
+        //A reaches C through both G1 and G2, so each game carries half of that pair
+        let edges = vec![
+            ("A".to_string(), "G1".to_string()),
+            ("A".to_string(), "G2".to_string()),
+            ("C".to_string(), "G1".to_string()),
+            ("C".to_string(), "G2".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let mut rng = StdRng::seed_from_u64(1);
+        let scores = graph.betweenness_centrality(None, &mut rng);
+        assert_eq!(scores[graph.node_id(NodeKind::Product, "G1").unwrap() as usize], 0.5);
+
+        //Sampling every vertex is the exact computation
+        assert_eq!(graph.betweenness_centrality(Some(10), &mut rng), scores);
+    }
+
+    #[test]
+    fn test_betweenness_is_deterministic() {
+        //The same sources give bit-identical scores on every run and with any number of threads
+        let graph = two_game_graph(500, 17, 23);
+        let run = || graph.betweenness_centrality(Some(300), &mut StdRng::seed_from_u64(5));
+        let bits = |scores: Vec<f64>| scores.iter().map(|b| b.to_bits()).collect::<Vec<u64>>();
+        let first = bits(run());
+        assert_eq!(bits(run()), first);
+        let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        assert_eq!(bits(single.install(run)), first);
+    }
+
+    #[test]
+    fn test_closeness_and_degree() {
+        let graph = bridge_graph();
+        let closeness = graph.closeness_centrality();
+        let degree = graph.degree_centrality();
+        let id = |kind, name| graph.node_id(kind, name).unwrap() as usize;
+        //G2 is at distances 1, 1, 2, 2, 3, 3 from the other six vertices
+        assert!((closeness[id(NodeKind::Product, "G2")] - 6.0 / 12.0).abs() < 1e-12);
+        assert!(closeness[id(NodeKind::Product, "G2")] > closeness[id(NodeKind::Reviewer, "A")]);
+        assert_eq!(degree[id(NodeKind::Reviewer, "B")], 2.0 / 3.0);
+        assert_eq!(degree[id(NodeKind::Product, "G1")], 2.0 / 4.0);
+    }
+
+    #[test]
+    fn test_pagerank_and_hits() {
+        let graph = bridge_graph();
+        let rank = graph.pagerank(0.85);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let id = |kind, name| graph.node_id(kind, name).unwrap() as usize;
+        assert!(rank[id(NodeKind::Reviewer, "B")] > rank[id(NodeKind::Reviewer, "A")]);
+        assert!((rank[id(NodeKind::Reviewer, "A")] - rank[id(NodeKind::Reviewer, "D")]).abs() < 1e-9);
+
+        let hits = graph.hits();
+        let reviewers: f64 = graph.reviewers().map(|u| hits[u as usize]).sum();
+        let products: f64 = graph.products().map(|u| hits[u as usize]).sum();
+        assert!((reviewers - 1.0).abs() < 1e-9 && (products - 1.0).abs() < 1e-9);
+        assert!(hits[id(NodeKind::Product, "G2")] > hits[id(NodeKind::Product, "G1")]);
+    }
+}
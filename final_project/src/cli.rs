@@ -0,0 +1,451 @@
+//Command-line interface for the review graph analysis
+//Every run reads one review dump, so the input path and output format are global
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use final_project::centrality::Centrality;
+use final_project::community::CommunityMethod;
+use final_project::dates::{parse_date, SECONDS_PER_DAY};
+use final_project::filter::EdgeFilter;
+use final_project::graph::NodeKind;
+use final_project::projection::Normalization;
+use final_project::sample::SampleMethod;
+use final_project::temporal::{Period, SnapshotMode, EXACT_PATH_LIMIT};
+use final_project::weighted::EdgeCost;
+
+#[derive(Parser, Debug)]
+#[command(name = "final_project", about = "Six degrees of separation on Amazon review graphs")]
+pub struct Cli {
+    /// Path to the JSON-lines review dump, plain or compressed with gzip, zstd or bzip2; - reads standard input
+    #[arg(short, long, global = true, default_value = "Video_Games_5.json.gz")]
+    pub input: String,
+
+    /// How results are printed
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Skip malformed lines and report them instead of stopping
+    #[arg(long, global = true)]
+    pub lenient: bool,
+
+    /// Number of worker threads for graph searches, all cores when omitted
+    #[arg(long, global = true)]
+    pub threads: Option<usize>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Count reviews, reviewers, games and edges in the full graph
+    Stats,
+    /// Average shortest path of one sample, or of the full graph without --sample-size
+    Paths(PathsArgs),
+    /// Print the reviewer/game edges of one sample
+    Sample(SampleArgs),
+    /// Draw several samples and compare their average shortest paths
+    Compare(CompareArgs),
+    /// Project the graph onto reviewers or games, linking pairs with shared neighbours
+    Project(ProjectArgs),
+    /// Estimate the average shortest path from a random subset of BFS sources
+    Estimate(EstimateArgs),
+    /// Count the connected components and their sizes
+    Components(ComponentsArgs),
+    /// Histogram of shortest path lengths with diameter, radius and eccentricities
+    Distances(DistancesArgs),
+    /// Show the chain of reviewers and games connecting two nodes
+    Path(PathArgs),
+    /// List every node within a number of hops of a seed set
+    Neighborhood(NeighborhoodArgs),
+    /// Share of pairs within a number of hops, searching no deeper than that
+    Within(WithinArgs),
+    /// Detect communities of reviewers and games
+    Communities(CommunitiesArgs),
+    /// Rank reviewers and games by a centrality measure
+    Centrality(CentralityArgs),
+    /// Degree distributions of reviewers and games with a power-law fit
+    Degrees(DegreesArgs),
+    /// Compare path length, clustering and component size with degree-preserving random graphs
+    NullModel(NullModelArgs),
+    /// Path length, components and density of the graph year by year or quarter by quarter
+    Snapshots(SnapshotsArgs),
+}
+
+impl Command {
+    //Graph options of the commands that load a graph
+    fn graph_mut(&mut self) -> Option<&mut GraphArgs> {
+        match self {
+            Command::Stats | Command::Sample(_) | Command::Compare(_) => None,
+            Command::Paths(args) => Some(&mut args.graph),
+            Command::Project(args) => Some(&mut args.graph),
+            Command::Estimate(args) => Some(&mut args.graph),
+            Command::Components(args) => Some(&mut args.graph),
+            Command::Distances(args) => Some(&mut args.graph),
+            Command::Path(args) => Some(&mut args.graph),
+            Command::Neighborhood(args) => Some(&mut args.graph),
+            Command::Within(args) => Some(&mut args.graph),
+            Command::Communities(args) => Some(&mut args.graph),
+            Command::Centrality(args) => Some(&mut args.graph),
+            Command::Degrees(args) => Some(&mut args.graph),
+            Command::NullModel(args) => Some(&mut args.graph),
+            Command::Snapshots(args) => Some(&mut args.graph),
+        }
+    }
+
+    //Seed option of the command when this run draws random numbers, None for deterministic runs
+    pub fn seed_mut(&mut self) -> Option<&mut Option<u64>> {
+        let draws = match self {
+            Command::Estimate(_) | Command::NullModel(_) | Command::Snapshots(_) => true,
+            Command::Centrality(args) => args.measure == Centrality::Betweenness && args.sources.is_some(),
+            _ => false,
+        };
+        match self {
+            Command::Sample(args) => Some(&mut args.seed),
+            Command::Compare(args) => Some(&mut args.sample.seed),
+            command => {
+                let graph = command.graph_mut()?;
+                (draws || graph.sample_size.is_some()).then_some(&mut graph.seed)
+            }
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    /// Number of reviewer IDs and ASINs to draw, or of vertices to keep for the other samplers
+    #[arg(short = 'n', long, default_value_t = 1500)]
+    pub sample_size: usize,
+
+    /// Seed for the random number generator; one is drawn and reported when omitted
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// How the sample is drawn
+    #[arg(long, value_enum, default_value_t = SampleMethod::Node)]
+    pub sampler: SampleMethod,
+}
+
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    /// Number of reviewer IDs and ASINs to draw, or of vertices to keep for the other samplers;
+    /// the full graph is used when omitted
+    #[arg(short = 'n', long)]
+    pub sample_size: Option<usize>,
+
+    /// Seed for the random number generator; one is drawn and reported when omitted
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// How the sample is drawn
+    #[arg(long, value_enum, default_value_t = SampleMethod::Node)]
+    pub sampler: SampleMethod,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+//Which reviews the analysed graph keeps, every review when none is given
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Only keep reviews rated at least this many stars
+    #[arg(long)]
+    pub min_rating: Option<f32>,
+
+    /// Only keep reviews rated at most this many stars
+    #[arg(long)]
+    pub max_rating: Option<f32>,
+
+    /// Only keep reviews of verified purchases
+    #[arg(long)]
+    pub verified: bool,
+
+    /// Only keep reviews written on or after this date (YYYY-MM-DD, UTC)
+    #[arg(long, value_parser = parse_date)]
+    pub since: Option<i64>,
+
+    /// Only keep reviews written on or before this date (YYYY-MM-DD, UTC)
+    #[arg(long, value_parser = parse_date)]
+    pub until: Option<i64>,
+}
+
+impl FilterArgs {
+    //The filter as a library EdgeFilter, --until includes the whole of its day
+    pub fn edge_filter(&self) -> EdgeFilter {
+        EdgeFilter {
+            min_rating: self.min_rating,
+            max_rating: self.max_rating,
+            verified_only: self.verified,
+            since: self.since,
+            until: self.until.map(|until| until + SECONDS_PER_DAY),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    #[command(flatten)]
+    pub sample: SampleArgs,
+
+    /// Number of independent samples to compare, at least 2 for a confidence interval
+    #[arg(short = 'k', long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(2..))]
+    pub samples: u64,
+
+    /// Only measure paths inside the largest connected component of each sample
+    #[arg(long)]
+    pub largest_component: bool,
+
+    /// Also draw samples with this sampler and test whether their average path differs
+    #[arg(long, value_enum)]
+    pub versus_sampler: Option<SampleMethod>,
+
+    /// Also draw samples of this size and test whether their average path differs
+    #[arg(long)]
+    pub versus_size: Option<usize>,
+
+    /// Random splits in the permutation test
+    #[arg(long, default_value_t = 10000)]
+    pub permutations: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct PathsArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Only measure paths inside the largest connected component
+    #[arg(long)]
+    pub largest_component: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ProjectArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Which side of the graph to project onto
+    #[arg(long, value_enum, default_value_t = NodeKind::Product)]
+    pub side: NodeKind,
+
+    /// How shared-neighbour counts are turned into edge weights
+    #[arg(long, value_enum, default_value_t = Normalization::Count)]
+    pub normalize: Normalization,
+
+    /// Smallest number of shared neighbours for a pair to be linked
+    #[arg(long, default_value_t = 1)]
+    pub min_overlap: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct EstimateArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Number of random BFS sources
+    #[arg(short = 'k', long, default_value_t = 1000)]
+    pub sources: usize,
+
+    /// Bootstrap resamples for the confidence interval, 0 for the normal approximation
+    #[arg(short, long, default_value_t = 1000)]
+    pub bootstrap: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentsArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Also list the component of every reviewer and game
+    #[arg(long)]
+    pub assignments: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DistancesArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Only measure paths inside the largest connected component
+    #[arg(long)]
+    pub largest_component: bool,
+
+    /// Also list the eccentricity of every reviewer and game
+    #[arg(long)]
+    pub eccentricity: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PathArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Reviewer ID or ASIN the path starts at
+    #[arg(long)]
+    pub from: String,
+
+    /// Whether --from names a reviewer or a game
+    #[arg(long, value_enum, default_value_t = NodeKind::Reviewer)]
+    pub from_kind: NodeKind,
+
+    /// Reviewer ID or ASIN the path ends at
+    #[arg(long)]
+    pub to: String,
+
+    /// Whether --to names a reviewer or a game
+    #[arg(long, value_enum, default_value_t = NodeKind::Reviewer)]
+    pub to_kind: NodeKind,
+
+    /// List every shortest path instead of one
+    #[arg(long)]
+    pub all: bool,
+
+    /// Most paths listed with --all
+    #[arg(long, default_value_t = 100)]
+    pub limit: usize,
+
+    /// Find the cheapest path under this edge cost instead of the fewest hops
+    #[arg(long, value_enum, conflicts_with = "all")]
+    pub cost: Option<EdgeCost>,
+}
+
+#[derive(Args, Debug)]
+pub struct NeighborhoodArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Reviewer ID or ASIN to start from, can be repeated
+    #[arg(long)]
+    pub seed_node: Vec<String>,
+
+    /// Whether --seed-node names reviewers or games
+    #[arg(long, value_enum, default_value_t = NodeKind::Reviewer)]
+    pub seed_kind: NodeKind,
+
+    /// Start from every reviewer of this ASIN, can be repeated
+    #[arg(long)]
+    pub reviewers_of: Vec<String>,
+
+    /// Largest number of hops from the seeds
+    #[arg(long, default_value_t = 2)]
+    pub hops: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct WithinArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Largest number of hops between a pair
+    #[arg(long, default_value_t = 6)]
+    pub hops: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct CommunitiesArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Community detection algorithm
+    #[arg(long, value_enum, default_value_t = CommunityMethod::Louvain)]
+    pub method: CommunityMethod,
+
+    /// Number of largest communities to summarise
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Number of most reviewed games listed per community
+    #[arg(long, default_value_t = 5)]
+    pub top: usize,
+
+    /// Also list the reviewer IDs in each community
+    #[arg(long)]
+    pub members: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CentralityArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Centrality measure to rank by
+    #[arg(long, value_enum, default_value_t = Centrality::PageRank)]
+    pub measure: Centrality,
+
+    /// Number of top reviewers and games listed
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Estimate betweenness from this many random BFS sources instead of all of them
+    #[arg(short = 'k', long)]
+    pub sources: Option<usize>,
+
+    /// PageRank damping factor
+    #[arg(long, default_value_t = 0.85)]
+    pub damping: f64,
+}
+
+#[derive(Args, Debug)]
+pub struct DegreesArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Also list how many vertices have each degree
+    #[arg(long)]
+    pub histogram: bool,
+
+    /// Write the complementary CDF of both sides to this CSV file
+    #[arg(long)]
+    pub ccdf: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct NullModelArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Number of rewired random graphs in the null ensemble
+    #[arg(short, long, default_value_t = 20)]
+    pub models: usize,
+
+    /// Edge swaps attempted per edge when rewiring
+    #[arg(long, default_value_t = 10)]
+    pub swaps: usize,
+
+    /// Only measure paths inside the largest connected component
+    #[arg(long)]
+    pub largest_component: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SnapshotsArgs {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Length of the periods between snapshots
+    #[arg(long, value_enum, default_value_t = Period::Year)]
+    pub period: Period,
+
+    /// Keep every review up to each period, or only those of the last --window periods
+    #[arg(long, value_enum, default_value_t = SnapshotMode::Cumulative)]
+    pub mode: SnapshotMode,
+
+    /// Number of periods a sliding snapshot covers
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub window: u64,
+
+    /// Only measure paths inside the largest connected component of each snapshot
+    #[arg(long)]
+    pub largest_component: bool,
+
+    /// Number of random BFS sources for the average path of a snapshot too large to search exactly
+    #[arg(short = 'k', long, default_value_t = 1000)]
+    pub sources: usize,
+
+    /// Largest number of vertices whose paths are searched from every vertex, larger snapshots are estimated
+    #[arg(long, default_value_t = EXACT_PATH_LIMIT)]
+    pub exact_limit: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
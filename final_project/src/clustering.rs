@@ -0,0 +1,77 @@
+//Clustering of the bipartite review graph
+//Reviews never form triangles, so clustering is measured with squares: two reviewers who share two games
+use crate::graph::{Graph, NodeId, NodeKind};
+
+impl Graph {
+    //Number of 4-cycles, counted as pairs of reviewers sharing pairs of games
+    //Shared games are counted in a dense array reset after each reviewer, as in projection
+    pub fn square_count(&self) -> u64 {
+        let mut shared = vec![0u64; self.node_count()];
+        let mut touched: Vec<NodeId> = Vec::new();
+        let mut squares = 0;
+        for u in self.nodes_of(NodeKind::Reviewer) {
+            for &game in self.neighbors(u) {
+                for &v in self.neighbors(game) {
+                    if v > u {
+                        if shared[v as usize] == 0 {
+                            touched.push(v);
+                        }
+                        shared[v as usize] += 1;
+                    }
+                }
+            }
+            for &v in &touched {
+                let s = shared[v as usize];
+                squares += s * (s - 1) / 2;
+                shared[v as usize] = 0;
+            }
+            touched.clear();
+        }
+        squares
+    }
+
+    //Robins-Alexander clustering: 4 * squares / paths of length 3
+    //It is the share of 3-paths closed into a square, 1 for a complete bipartite graph,
+    //and 0 when there are no 3-paths at all
+    pub fn bipartite_clustering(&self) -> f64 {
+        let paths: u64 = self
+            .nodes()
+            .flat_map(|u| self.neighbors(u).iter().filter(move |&&v| u < v).map(move |&v| (u, v)))
+            .map(|(u, v)| (self.degree(u) as u64 - 1) * (self.degree(v) as u64 - 1))
+            .sum();
+        if paths == 0 {
+            0.0
+        } else {
+            4.0 * self.square_count() as f64 / paths as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bipartite_clustering() {
+        //Reviewers A and B both reviewed G1, G2 and G3, a complete bipartite graph
+        let complete: Vec<(String, String)> = ["A", "B"]
+            .iter()
+            .flat_map(|r| ["G1", "G2", "G3"].iter().map(move |g| (r.to_string(), g.to_string())))
+            .collect();
+        let graph = Graph::create_undirected(&complete);
+        assert_eq!(graph.square_count(), 3);
+        assert_eq!(graph.bipartite_clustering(), 1.0);
+
+        //Reviewer C of G3 and G4 opens 3-paths that no square closes
+        let mut edges = complete.clone();
+        edges.push(("C".to_string(), "G3".to_string()));
+        edges.push(("C".to_string(), "G4".to_string()));
+        let graph = Graph::create_undirected(&edges);
+        assert_eq!(graph.square_count(), 3);
+        //3-paths: the 4 edges A/B - G1/G2 give 2 * 1 each, A/B - G3 give 2 * 2 each, C - G3 gives 1 * 2
+        assert_eq!(graph.bipartite_clustering(), 12.0 / 18.0);
+
+        let path = vec![("A".to_string(), "G1".to_string()), ("B".to_string(), "G1".to_string())];
+        assert_eq!(Graph::create_undirected(&path).bipartite_clustering(), 0.0);
+    }
+}
@@ -0,0 +1,434 @@
+//Community detection on the review graph
+//Louvain optimises the standard (Newman) modularity, bipartite label propagation (LPAb)
+//optimises Barber's bipartite modularity, which only rewards reviewer-game edges
+use crate::graph::{Graph, NodeId, NodeKind};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+
+//Which algorithm assigns the communities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommunityMethod {
+    Louvain,
+    LabelPropagation,
+}
+
+//A partition of the vertices into communities
+//Communities are numbered by decreasing size, ties broken by their smallest NodeId
+#[derive(Debug, Clone, Serialize)]
+pub struct Communities {
+    pub method: CommunityMethod,
+    pub labels: Vec<u32>,
+    pub sizes: Vec<usize>,
+    pub modularity: f64,
+    pub bipartite_modularity: f64,
+}
+
+//Members of one community and the games its reviewers reviewed most
+//top_products holds (game, number of reviews from members), most reviewed first
+#[derive(Debug, Clone, Serialize)]
+pub struct CommunitySummary {
+    pub community: usize,
+    pub reviewers: Vec<NodeId>,
+    pub products: Vec<NodeId>,
+    pub top_products: Vec<(NodeId, usize)>,
+}
+
+impl Communities {
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn community_of(&self, u: NodeId) -> usize {
+        self.labels[u as usize] as usize
+    }
+
+    //Summaries of every community, listing up to top games each
+    pub fn summaries(&self, graph: &Graph, top: usize) -> Vec<CommunitySummary> {
+        let mut summaries: Vec<CommunitySummary> = (0..self.count())
+            .map(|community| CommunitySummary {
+                community,
+                reviewers: Vec::new(),
+                products: Vec::new(),
+                top_products: Vec::new(),
+            })
+            .collect();
+        for u in graph.nodes() {
+            let summary = &mut summaries[self.community_of(u)];
+            match graph.kind(u) {
+                NodeKind::Reviewer => summary.reviewers.push(u),
+                NodeKind::Product => summary.products.push(u),
+            }
+        }
+        for summary in summaries.iter_mut() {
+            let mut counts: HashMap<NodeId, usize> = HashMap::new();
+            for &reviewer in &summary.reviewers {
+                for &game in graph.neighbors(reviewer) {
+                    *counts.entry(game).or_insert(0) += 1;
+                }
+            }
+            let mut ranked: Vec<(NodeId, usize)> = counts.into_iter().collect();
+            ranked.sort_by_key(|&(game, count)| (std::cmp::Reverse(count), game));
+            ranked.truncate(top);
+            summary.top_products = ranked;
+        }
+        summaries
+    }
+}
+
+//Renumbers arbitrary labels 0.. by decreasing community size, ties by smallest member
+fn renumber(labels: &[u32]) -> (Vec<u32>, Vec<usize>) {
+    let mut found: HashMap<u32, (usize, usize)> = HashMap::new();
+    for (u, &label) in labels.iter().enumerate() {
+        found.entry(label).or_insert((0, u)).0 += 1;
+    }
+    let mut ranking: Vec<(u32, usize, usize)> = found.into_iter().map(|(label, (size, first))| (label, size, first)).collect();
+    ranking.sort_by_key(|&(_, size, first)| (std::cmp::Reverse(size), first));
+    let rank: HashMap<u32, u32> = ranking.iter().enumerate().map(|(i, &(label, _, _))| (label, i as u32)).collect();
+    let labels = labels.iter().map(|label| rank[label]).collect();
+    let sizes = ranking.iter().map(|&(_, size, _)| size).collect();
+    (labels, sizes)
+}
+
+//Weighted graph used by the Louvain levels
+//adjacency[i] lists (j, A_ij) including the self-loop (i, A_ii) of merged vertices,
+//where A_ii sums the ordered pairs inside i so that degree[i] = sum over j of A_ij
+struct WeightedGraph {
+    adjacency: Vec<Vec<(usize, f64)>>,
+    degree: Vec<f64>,
+    total: f64,
+}
+
+impl WeightedGraph {
+    fn from_graph(graph: &Graph) -> WeightedGraph {
+        let adjacency: Vec<Vec<(usize, f64)>> = graph
+            .nodes()
+            .map(|u| graph.neighbors(u).iter().map(|&v| (v as usize, 1.0)).collect())
+            .collect();
+        WeightedGraph::new(adjacency)
+    }
+
+    fn new(adjacency: Vec<Vec<(usize, f64)>>) -> WeightedGraph {
+        let degree: Vec<f64> = adjacency.iter().map(|row| row.iter().map(|&(_, w)| w).sum()).collect();
+        let total = degree.iter().sum();
+        WeightedGraph { adjacency, degree, total }
+    }
+
+    //Moves single vertices between communities while that raises the modularity
+    //Returns the community of every vertex, numbered 0.. in order of first appearance
+    fn local_moving(&self) -> (Vec<usize>, bool) {
+        let n = self.adjacency.len();
+        let mut community: Vec<usize> = (0..n).collect();
+        let mut totals = self.degree.clone();
+        let mut links = vec![0.0; n];
+        let mut touched: Vec<usize> = Vec::new();
+        let mut moved_any = false;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..n {
+                let own = community[i];
+                let k = self.degree[i];
+                totals[own] -= k;
+                for &(j, w) in &self.adjacency[i] {
+                    if j != i {
+                        let c = community[j];
+                        if links[c] == 0.0 {
+                            touched.push(c);
+                        }
+                        links[c] += w;
+                    }
+                }
+                //Gain of joining c, up to a factor shared by every candidate
+                //Staying put wins ties, which guarantees the passes terminate
+                let gain = |c: usize, links: &[f64]| links[c] - totals[c] * k / self.total;
+                let mut best = own;
+                let mut best_gain = gain(own, &links);
+                for &c in &touched {
+                    let g = gain(c, &links);
+                    if g > best_gain + 1e-12 {
+                        best = c;
+                        best_gain = g;
+                    }
+                }
+                for &c in &touched {
+                    links[c] = 0.0;
+                }
+                touched.clear();
+                totals[best] += k;
+                if best != own {
+                    community[i] = best;
+                    improved = true;
+                    moved_any = true;
+                }
+            }
+        }
+
+        let mut index: HashMap<usize, usize> = HashMap::new();
+        let dense = community
+            .iter()
+            .map(|&c| {
+                let next = index.len();
+                *index.entry(c).or_insert(next)
+            })
+            .collect();
+        (dense, moved_any)
+    }
+
+    //Merges every community into a single vertex
+    fn aggregate(&self, community: &[usize], count: usize) -> WeightedGraph {
+        let mut rows: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+        for (i, row) in self.adjacency.iter().enumerate() {
+            for &(j, w) in row {
+                *rows[community[i]].entry(community[j]).or_insert(0.0) += w;
+            }
+        }
+        let adjacency = rows
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<(usize, f64)> = row.into_iter().collect();
+                row.sort_by_key(|&(j, _)| j);
+                row
+            })
+            .collect();
+        WeightedGraph::new(adjacency)
+    }
+}
+
+impl Graph {
+    //Standard modularity of a partition, treating reviewers and games alike
+    pub fn modularity(&self, labels: &[u32]) -> f64 {
+        let m = self.edge_count() as f64;
+        if m == 0.0 {
+            return 0.0;
+        }
+        let mut internal: HashMap<u32, f64> = HashMap::new();
+        let mut degrees: HashMap<u32, f64> = HashMap::new();
+        for u in self.nodes() {
+            let label = labels[u as usize];
+            *degrees.entry(label).or_insert(0.0) += self.degree(u) as f64;
+            for &v in self.neighbors(u) {
+                if u < v && labels[v as usize] == label {
+                    *internal.entry(label).or_insert(0.0) += 1.0;
+                }
+            }
+        }
+        let inside: f64 = internal.values().sum::<f64>() / m;
+        let expected: f64 = degrees.values().map(|d| (d / (2.0 * m)).powi(2)).sum();
+        inside - expected
+    }
+
+    //Barber's bipartite modularity: only reviewer-game pairs count,
+    //with the null model k_r * k_g / m of a random bipartite graph
+    pub fn bipartite_modularity(&self, labels: &[u32]) -> f64 {
+        let m = self.edge_count() as f64;
+        if m == 0.0 {
+            return 0.0;
+        }
+        let mut inside = 0.0;
+        let mut reviewer_degrees: HashMap<u32, f64> = HashMap::new();
+        let mut product_degrees: HashMap<u32, f64> = HashMap::new();
+        for u in self.nodes() {
+            let label = labels[u as usize];
+            match self.kind(u) {
+                NodeKind::Reviewer => {
+                    *reviewer_degrees.entry(label).or_insert(0.0) += self.degree(u) as f64;
+                    inside += self.neighbors(u).iter().filter(|&&v| labels[v as usize] == label).count() as f64;
+                }
+                NodeKind::Product => *product_degrees.entry(label).or_insert(0.0) += self.degree(u) as f64,
+            }
+        }
+        let expected: f64 = reviewer_degrees
+            .iter()
+            .map(|(label, kr)| kr * product_degrees.get(label).copied().unwrap_or(0.0))
+            .sum();
+        inside / m - expected / (m * m)
+    }
+
+    //Multi-level Louvain: local moving, then merging communities into vertices, until nothing moves
+    fn louvain_labels(&self) -> Vec<u32> {
+        let mut labels: Vec<usize> = self.nodes().map(|u| u as usize).collect();
+        let mut level = WeightedGraph::from_graph(self);
+        if level.total == 0.0 {
+            return labels.into_iter().map(|l| l as u32).collect();
+        }
+        loop {
+            let (community, moved) = level.local_moving();
+            if !moved {
+                break;
+            }
+            for label in labels.iter_mut() {
+                *label = community[*label];
+            }
+            let count = community.iter().max().map_or(0, |&c| c + 1);
+            level = level.aggregate(&community, count);
+        }
+        labels.into_iter().map(|l| l as u32).collect()
+    }
+
+    //LPAb: every vertex starts with its own NodeId as label, then games and reviewers take turns
+    //adopting the neighbouring label with the largest gain in bipartite modularity,
+    //sum of edges to the label minus k_u * (degree of the other side holding it) / m
+    //A vertex keeps its label unless another one scores strictly higher, and among equally
+    //good other labels the smallest wins, so the result is deterministic
+    fn label_propagation_labels(&self) -> Vec<u32> {
+        let m = self.edge_count() as f64;
+        let mut labels: Vec<u32> = self.nodes().collect();
+        if m == 0.0 {
+            return labels;
+        }
+        let mut side_degree: [HashMap<u32, f64>; 2] = [HashMap::new(), HashMap::new()];
+        for u in self.nodes() {
+            *side_degree[self.kind(u) as usize].entry(labels[u as usize]).or_insert(0.0) += self.degree(u) as f64;
+        }
+
+        let max_rounds = 100;
+        let mut kind = NodeKind::Product;
+        let mut stable_sides = 0;
+        for _ in 0..max_rounds * 2 {
+            let other = kind.other() as usize;
+            let mut changed = false;
+            let nodes: Vec<NodeId> = self.nodes_of(kind).collect();
+            for u in nodes {
+                let k = self.degree(u) as f64;
+                if k == 0.0 {
+                    continue;
+                }
+                let mut counts: HashMap<u32, f64> = HashMap::new();
+                for &v in self.neighbors(u) {
+                    *counts.entry(labels[v as usize]).or_insert(0.0) += 1.0;
+                }
+                let current = labels[u as usize];
+                let score = |label: u32, count: f64| count - k * side_degree[other].get(&label).copied().unwrap_or(0.0) / m;
+                let mut best = (current, score(current, counts.get(&current).copied().unwrap_or(0.0)));
+                let mut candidates: Vec<(u32, f64)> = counts.into_iter().collect();
+                candidates.sort_by_key(|&(label, _)| label);
+                for (label, count) in candidates {
+                    let s = score(label, count);
+                    if s > best.1 + 1e-12 {
+                        best = (label, s);
+                    }
+                }
+                if best.0 != current {
+                    let own = self.kind(u) as usize;
+                    *side_degree[own].entry(current).or_insert(0.0) -= k;
+                    *side_degree[own].entry(best.0).or_insert(0.0) += k;
+                    labels[u as usize] = best.0;
+                    changed = true;
+                }
+            }
+            stable_sides = if changed { 0 } else { stable_sides + 1 };
+            if stable_sides >= 2 {
+                break;
+            }
+            kind = kind.other();
+        }
+        labels
+    }
+
+    //Detects communities and scores the partition with both modularities
+    pub fn communities(&self, method: CommunityMethod) -> Communities {
+        let raw = match method {
+            CommunityMethod::Louvain => self.louvain_labels(),
+            CommunityMethod::LabelPropagation => self.label_propagation_labels(),
+        };
+        let (labels, sizes) = renumber(&raw);
+        Communities {
+            method,
+            modularity: self.modularity(&labels),
+            bipartite_modularity: self.bipartite_modularity(&labels),
+            labels,
+            sizes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //Two groups of three reviewers and three games, every reviewer reviewed every game in
+    //their group, plus a single review R1 -> H1 bridging the groups
+    fn two_groups() -> Graph {
+        let mut edges = Vec::new();
+        for group in ["G", "H"] {
+            for r in 1..=3 {
+                for g in 1..=3 {
+                    edges.push((format!("{}R{}", group, r), format!("{}{}", group, g)));
+                }
+            }
+        }
+        edges.push(("GR1".to_string(), "H1".to_string()));
+        Graph::create_undirected(&edges)
+    }
+
+    fn assert_two_groups(graph: &Graph, communities: &Communities) {
+        assert_eq!(communities.count(), 2);
+        assert_eq!(communities.sizes, vec![6, 6]);
+        for u in graph.nodes() {
+            let group = &graph.name(u)[..1];
+            let same = graph.nodes().filter(|&v| &graph.name(v)[..1] == group);
+            assert!(same.into_iter().all(|v| communities.community_of(v) == communities.community_of(u)));
+        }
+    }
+
+    #[test]
+    fn test_louvain() {
+        let graph = two_groups();
+        let communities = graph.communities(CommunityMethod::Louvain);
+        assert_two_groups(&graph, &communities);
+        //18 of 19 edges inside, each group holding half of the degree
+        assert!((communities.modularity - (18.0 / 19.0 - 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let graph = two_groups();
+        let communities = graph.communities(CommunityMethod::LabelPropagation);
+        assert_two_groups(&graph, &communities);
+        assert!(communities.bipartite_modularity > 0.4);
+    }
+
+    #[test]
+    fn test_label_propagation_ties() {
+        let pairs = |edges: &[(&str, &str)]| -> Vec<(String, String)> {
+            edges.iter().map(|&(r, g)| (r.to_string(), g.to_string())).collect()
+        };
+        //On its own, G gains nothing by joining A or B and nobody gains by joining G,
+        //so every vertex keeps the label it started with
+        let alone = Graph::create_undirected(&pairs(&[("A", "G"), ("B", "G")]));
+        assert_eq!(alone.label_propagation_labels(), vec![0, 1, 2]);
+
+        //Next to a larger star, G gains the same by joining A or B and takes the smaller label,
+        //the NodeId of A, which B then adopts as well
+        let graph = Graph::create_undirected(&pairs(&[("A", "G"), ("B", "G"), ("C", "H"), ("D", "H"), ("E", "H"), ("F", "H")]));
+        let id = |kind, name| graph.node_id(kind, name).unwrap();
+        let labels = graph.label_propagation_labels();
+        let a = id(NodeKind::Reviewer, "A");
+        assert_eq!(labels[id(NodeKind::Product, "G") as usize], a);
+        assert_eq!(labels[id(NodeKind::Reviewer, "B") as usize], a);
+        assert_eq!(labels[id(NodeKind::Product, "H") as usize], id(NodeKind::Reviewer, "C"));
+        assert_eq!(graph.label_propagation_labels(), labels);
+    }
+
+    #[test]
+    fn test_community_summaries() {
+        let graph = two_groups();
+        let communities = graph.communities(CommunityMethod::Louvain);
+        let r1 = graph.node_id(NodeKind::Reviewer, "GR1").unwrap();
+        let summary = &communities.summaries(&graph, 2)[communities.community_of(r1)];
+        assert_eq!(summary.reviewers.len(), 3);
+        assert_eq!(summary.products.len(), 3);
+        assert_eq!(summary.top_products.len(), 2);
+        assert!(summary.top_products.iter().all(|&(_, count)| count == 3));
+    }
+
+    #[test]
+    fn test_modularity_of_single_community() {
+        let graph = two_groups();
+        let labels = vec![0; graph.node_count()];
+        assert!(graph.modularity(&labels).abs() < 1e-12);
+        assert!(graph.bipartite_modularity(&labels).abs() < 1e-12);
+    }
+}
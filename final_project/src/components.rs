@@ -0,0 +1,141 @@
+//Connected components of the review graph
+//Path lengths are only meaningful inside a component, so the samples are checked for fragmentation first
+use crate::graph::{bfs_visit, Graph, NodeId, ReviewGraph, UNREACHABLE};
+use std::collections::BTreeMap;
+
+//Component of every vertex and the size of every component
+//Components are numbered by decreasing size, ties broken by their smallest NodeId,
+//so component 0 is always the largest
+#[derive(Debug, Clone)]
+pub struct Components {
+    labels: Vec<u32>,
+    sizes: Vec<usize>,
+}
+
+impl Components {
+    //Number of components, isolated vertices count as components of size 1
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    //Component of vertex u
+    pub fn component_of(&self, u: NodeId) -> usize {
+        self.labels[u as usize] as usize
+    }
+
+    //Component of every vertex, indexed by NodeId
+    pub fn labels(&self) -> &[u32] {
+        &self.labels
+    }
+
+    //Size of every component, largest first
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    //Size of the largest component, 0 for an empty graph
+    pub fn largest_size(&self) -> usize {
+        self.sizes.first().copied().unwrap_or(0)
+    }
+
+    //Number of components of each size
+    pub fn size_distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for &size in &self.sizes {
+            *distribution.entry(size).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    //Vertices of component c in NodeId order
+    pub fn members(&self, c: usize) -> Vec<NodeId> {
+        (0..self.labels.len() as NodeId)
+            .filter(|&u| self.labels[u as usize] as usize == c)
+            .collect()
+    }
+}
+
+//Labels the connected components with one BFS per unvisited vertex
+pub(crate) fn label_components<G: ReviewGraph + ?Sized>(graph: &G) -> Components {
+    let n = graph.node_count();
+    let mut distances = vec![UNREACHABLE; n];
+    let mut order = Vec::new();
+    let mut found: Vec<(usize, NodeId)> = Vec::new();
+    let mut labels = vec![0u32; n];
+    for node in graph.nodes() {
+        if distances[node as usize] == UNREACHABLE {
+            bfs_visit(graph, node, &mut distances, &mut order);
+            for &visited in &order {
+                labels[visited as usize] = found.len() as u32;
+            }
+            found.push((order.len(), node));
+        }
+    }
+
+    //Renumber by decreasing size, components were found in order of their smallest vertex
+    let mut ranking: Vec<usize> = (0..found.len()).collect();
+    ranking.sort_by_key(|&c| (std::cmp::Reverse(found[c].0), found[c].1));
+    let mut rank = vec![0u32; found.len()];
+    for (new, &old) in ranking.iter().enumerate() {
+        rank[old] = new as u32;
+    }
+    for label in labels.iter_mut() {
+        *label = rank[*label as usize];
+    }
+    let sizes = ranking.iter().map(|&c| found[c].0).collect();
+    Components { labels, sizes }
+}
+
+impl Graph {
+    //The largest connected component as a graph of its own
+    pub fn largest_component(&self) -> Graph {
+        let components = self.components();
+        if components.count() == 0 {
+            return Graph::new();
+        }
+        self.induced_subgraph(&components.members(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::NodeKind;
+
+    #[test]
+    fn test_components() {
+        //A path A - B - C - D, an edge E - F and a second edge G - F
+        let edges = vec![
+            ("E".to_string(), "F".to_string()),
+            ("A".to_string(), "B".to_string()),
+            ("C".to_string(), "B".to_string()),
+            ("C".to_string(), "D".to_string()),
+            ("G".to_string(), "H".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let components = graph.components();
+        assert_eq!(components.count(), 3);
+        assert_eq!(components.sizes(), &[4, 2, 2]);
+        assert_eq!(components.size_distribution(), BTreeMap::from([(2, 2), (4, 1)]));
+
+        let id = |kind, name| graph.node_id(kind, name).unwrap();
+        assert_eq!(components.component_of(id(NodeKind::Reviewer, "A")), 0);
+        assert_eq!(components.component_of(id(NodeKind::Product, "F")), 1);
+        assert_eq!(components.component_of(id(NodeKind::Reviewer, "G")), 2);
+        assert_eq!(components.members(1).len(), 2);
+
+        let largest = graph.largest_component();
+        assert_eq!(largest.node_count(), 4);
+        assert_eq!(largest.edge_count(), 3);
+        assert!(largest.node_id(NodeKind::Reviewer, "E").is_none());
+        assert_eq!(largest.average_shortpath().average, Some(20.0 / 12.0));
+    }
+
+    #[test]
+    fn test_components_of_empty_graph() {
+        let graph = Graph::create_undirected(&[]);
+        assert_eq!(graph.components().count(), 0);
+        assert_eq!(graph.components().largest_size(), 0);
+        assert_eq!(graph.largest_component().node_count(), 0);
+    }
+}
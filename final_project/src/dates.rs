@@ -0,0 +1,80 @@
+//Calendar dates of review timestamps, in UTC
+//The dumps store seconds since the Unix epoch, the command line takes dates as YYYY-MM-DD
+//Conversions use Howard Hinnant's days_from_civil algorithms for the proleptic Gregorian calendar
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+//Days since 1970-01-01 of a year, month (1 to 12) and day
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+//Year, month and day of a number of days since 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+//Year, month and day of a Unix timestamp
+pub fn civil_from_timestamp(timestamp: i64) -> (i64, u32, u32) {
+    civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY))
+}
+
+//Unix timestamp of midnight UTC at the start of a YYYY-MM-DD date
+pub fn parse_date(text: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid date {:?}, expected YYYY-MM-DD", text);
+    let mut parts = text.trim().splitn(3, '-');
+    let mut next = || parts.next().ok_or_else(invalid);
+    let year: i64 = next()?.parse().map_err(|_| invalid())?;
+    let month: u32 = next()?.parse().map_err(|_| invalid())?;
+    let day: u32 = next()?.parse().map_err(|_| invalid())?;
+    let days = days_from_civil(year, month, day);
+    //Reject dates such as 2014-02-30 that do not survive the round trip
+    if !(1..=12).contains(&month) || civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+    Ok(days * SECONDS_PER_DAY)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in (-800_000..800_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        //The reviewTime of the schema example, "09 13, 2009"
+        assert_eq!(civil_from_timestamp(1_252_800_000), (2009, 9, 13));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2009-09-13"), Ok(1_252_800_000));
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert!(parse_date("2014-02-30").is_err());
+        assert!(parse_date("2014-13-01").is_err());
+        assert!(parse_date("2014-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+}
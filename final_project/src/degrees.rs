@@ -0,0 +1,177 @@
+//Degree distributions of reviewers and games and a power-law fit of their tails
+//A sample that keeps the structure of the full graph should keep the shape of these tails
+use crate::graph::NodeKind;
+use crate::stats::{mean, percentile};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+//Smallest tail a power law is fitted to, shorter tails give meaningless exponents
+pub const MIN_TAIL: usize = 10;
+
+//Discrete power law P(k) ~ k^-alpha for degrees k >= xmin
+//ks is the Kolmogorov-Smirnov distance between the tail and the fitted law, smaller is better
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerLawFit {
+    pub alpha: f64,
+    pub xmin: usize,
+    pub tail: usize,
+    pub ks: f64,
+}
+
+//Summary of the degrees on one side of the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct DegreeStats {
+    pub kind: NodeKind,
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub min: usize,
+    pub max: usize,
+    pub histogram: BTreeMap<usize, usize>,
+    pub fit: Option<PowerLawFit>,
+}
+
+impl DegreeStats {
+    //Summarises a list of degrees, fitting a power law when there are enough of them
+    pub fn new(kind: NodeKind, degrees: &[usize]) -> DegreeStats {
+        let mut sorted = degrees.to_vec();
+        sorted.sort_unstable();
+        let values: Vec<f64> = sorted.iter().map(|&d| d as f64).collect();
+        let mut histogram = BTreeMap::new();
+        for &d in &sorted {
+            *histogram.entry(d).or_insert(0) += 1;
+        }
+        DegreeStats {
+            kind,
+            count: sorted.len(),
+            mean: mean(&values),
+            median: percentile(&values, 0.5),
+            min: sorted.first().copied().unwrap_or(0),
+            max: sorted.last().copied().unwrap_or(0),
+            histogram,
+            fit: fit_power_law(&sorted),
+        }
+    }
+
+    //Complementary CDF: for every observed degree k, the share of vertices with degree at least k
+    pub fn ccdf(&self) -> Vec<(usize, f64)> {
+        let mut remaining = self.count;
+        self.histogram
+            .iter()
+            .map(|(&degree, &vertices)| {
+                let share = remaining as f64 / self.count as f64;
+                remaining -= vertices;
+                (degree, share)
+            })
+            .collect()
+    }
+}
+
+//Share of a power law with exponent alpha starting at xmin that lies at or above x
+//Uses the continuous approximation of the discrete law from Clauset, Shalizi and Newman (2009)
+fn power_law_ccdf(x: usize, xmin: usize, alpha: f64) -> f64 {
+    ((x as f64 - 0.5) / (xmin as f64 - 0.5)).powf(1.0 - alpha)
+}
+
+//Fits a power law to the tail of sorted degrees starting at xmin
+//alpha is the approximate discrete maximum likelihood estimate 1 + n / sum ln(x / (xmin - 1/2))
+fn fit_tail(tail: &[usize], xmin: usize) -> PowerLawFit {
+    let n = tail.len() as f64;
+    let log_sum: f64 = tail.iter().map(|&x| (x as f64 / (xmin as f64 - 0.5)).ln()).sum();
+    let alpha = 1.0 + n / log_sum;
+
+    //Both CDFs are step functions on the integers, and the empirical one is flat between observed
+    //degrees, so the largest gap is at an observed degree or just before the next one
+    let mut ks: f64 = 0.0;
+    let mut i = 0;
+    while i < tail.len() {
+        let x = tail[i];
+        while i < tail.len() && tail[i] == x {
+            i += 1;
+        }
+        let empirical = i as f64 / n;
+        let last = if i < tail.len() { tail[i] - 1 } else { x };
+        for point in [x, last] {
+            let model = 1.0 - power_law_ccdf(point + 1, xmin, alpha);
+            ks = ks.max((empirical - model).abs());
+        }
+    }
+    PowerLawFit { alpha, xmin, tail: tail.len(), ks }
+}
+
+//Fits a power law to sorted degrees, choosing xmin to minimise the KS distance
+//Every observed degree leaving at least MIN_TAIL values in the tail is tried as xmin,
+//None when no such degree exists or every tail is a single repeated value
+pub fn fit_power_law(sorted: &[usize]) -> Option<PowerLawFit> {
+    let mut best: Option<PowerLawFit> = None;
+    let mut start = sorted.partition_point(|&d| d == 0);
+    while sorted.len() - start >= MIN_TAIL {
+        let xmin = sorted[start];
+        let tail = &sorted[start..];
+        if tail.last() != Some(&xmin) {
+            let fit = fit_tail(tail, xmin);
+            if best.as_ref().is_none_or(|b| fit.ks < b.ks) {
+                best = Some(fit);
+            }
+        }
+        start = sorted.partition_point(|&d| d <= xmin);
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Graph, ReviewGraph};
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_degree_stats() {
+        let edges = vec![
+            ("A".to_string(), "G1".to_string()),
+            ("A".to_string(), "G2".to_string()),
+            ("A".to_string(), "G3".to_string()),
+            ("B".to_string(), "G1".to_string()),
+            ("C".to_string(), "G1".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let reviewers = graph.degree_stats(NodeKind::Reviewer);
+        assert_eq!(reviewers.count, 3);
+        assert_eq!(reviewers.mean, 5.0 / 3.0);
+        assert_eq!(reviewers.median, 1.0);
+        assert_eq!((reviewers.min, reviewers.max), (1, 3));
+        assert_eq!(reviewers.histogram, BTreeMap::from([(1, 2), (3, 1)]));
+        assert_eq!(reviewers.ccdf(), vec![(1, 1.0), (3, 1.0 / 3.0)]);
+        assert!(reviewers.fit.is_none());
+
+        let games = graph.degree_stats(NodeKind::Product);
+        assert_eq!(games.ccdf(), vec![(1, 1.0), (3, 1.0 / 3.0)]);
+    }
+
+    #[test]
+    fn test_power_law_fit_recovers_exponent() {
+        //Inverse transform sampling of a continuous power law with alpha 2.5, rounded to integers,
+        //mixed with a uniform body below 5 that the xmin selection has to cut off
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut degrees: Vec<usize> = (0..20000)
+            .map(|_| {
+                let u: f64 = rng.gen();
+                (4.5 * (1.0 - u).powf(-1.0 / 1.5) + 0.5).floor() as usize
+            })
+            .collect();
+        degrees.extend((0..3000).map(|_| rng.gen_range(1..5)));
+        degrees.sort_unstable();
+        let fit = fit_power_law(&degrees).unwrap();
+        assert!((fit.alpha - 2.5).abs() < 0.1, "alpha = {}", fit.alpha);
+        assert!(fit.xmin >= 4 && fit.xmin <= 8, "xmin = {}", fit.xmin);
+        assert!(fit.ks < 0.05);
+        assert!(fit.tail >= MIN_TAIL);
+    }
+
+    #[test]
+    fn test_power_law_needs_a_tail() {
+        assert!(fit_power_law(&[1, 2, 3]).is_none());
+        assert!(fit_power_law(&[4; 50]).is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//Distribution of shortest path lengths, diameter and eccentricities
+//"Six degrees" is a statement about the tail of the distribution, not only its mean
+use crate::graph::{bfs_visit, NodeId, ReviewGraph, UNREACHABLE};
+use rayon::prelude::*;
+use serde::Serialize;
+
+//Share of all ordered pairs that must lie within 6 hops for six degrees of separation to hold,
+//the same 90% the effective diameter is taken at
+pub const SIX_DEGREES_SHARE: f64 = 0.9;
+
+//Shortest path statistics over the ordered pairs of distinct vertices
+//average is over connected pairs only and None when no pair is connected
+//efficiency is the mean of 1/distance over all pairs, 0 for unreachable ones,
+//and harmonic_distance its inverse, None when no pair is connected
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathStats {
+    pub nodes: usize,
+    pub pairs: u64,
+    pub connected_pairs: u64,
+    pub average: Option<f64>,
+    pub unreachable_fraction: f64,
+    pub efficiency: f64,
+    pub harmonic_distance: Option<f64>,
+}
+
+//Shortest path lengths from a set of BFS sources
+//histogram[d] counts the ordered pairs at distance d, histogram[0] is always 0
+//eccentricities[i] is the largest finite distance from sources[i], 0 for an isolated vertex
+#[derive(Debug, Clone, Serialize)]
+pub struct DistanceDistribution {
+    pub nodes: usize,
+    pub histogram: Vec<u64>,
+    pub sources: Vec<NodeId>,
+    pub eccentricities: Vec<u32>,
+}
+
+impl DistanceDistribution {
+    //Ordered pairs of distinct vertices
+    pub fn pairs(&self) -> u64 {
+        (self.nodes as u64) * (self.nodes as u64).saturating_sub(1)
+    }
+
+    pub fn connected_pairs(&self) -> u64 {
+        self.histogram.iter().sum()
+    }
+
+    //Largest finite distance between any two vertices
+    pub fn diameter(&self) -> u32 {
+        self.eccentricities.iter().copied().max().unwrap_or(0)
+    }
+
+    //Smallest eccentricity among the sources that reach at least one other vertex
+    //For a disconnected graph this is the radius of the most central component
+    pub fn radius(&self) -> u32 {
+        self.eccentricities.iter().copied().filter(|&e| e > 0).min().unwrap_or(0)
+    }
+
+    //Share of connected pairs at distance k or less, None when no pair is connected
+    pub fn share_within(&self, k: u32) -> Option<f64> {
+        let connected = self.connected_pairs();
+        let within: u64 = self.histogram.iter().take(k as usize + 1).sum();
+        (connected > 0).then(|| within as f64 / connected as f64)
+    }
+
+    //Share of all ordered pairs of distinct vertices at distance k or less, None without pairs
+    //Unlike share_within, unreachable pairs count as further than k
+    pub fn share_of_pairs_within(&self, k: u32) -> Option<f64> {
+        let pairs = self.pairs();
+        let within: u64 = self.histogram.iter().take(k as usize + 1).sum();
+        (pairs > 0).then(|| within as f64 / pairs as f64)
+    }
+
+    //Six degrees of separation hold when at least SIX_DEGREES_SHARE of all ordered pairs are
+    //within 6 hops, so a fragmented graph fails however short its connected paths are
+    pub fn six_degrees(&self) -> bool {
+        self.share_of_pairs_within(6).is_some_and(|share| share >= SIX_DEGREES_SHARE)
+    }
+
+    //Distance within which a share q of the connected pairs lie,
+    //linearly interpolated between whole hops, None when no pair is connected
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let connected = self.connected_pairs();
+        if connected == 0 {
+            return None;
+        }
+        let target = q.clamp(0.0, 1.0) * connected as f64;
+        let mut below = 0.0;
+        for (d, &count) in self.histogram.iter().enumerate().skip(1) {
+            let upto = below + count as f64;
+            if upto >= target && count > 0 {
+                return Some((d - 1) as f64 + (target - below) / count as f64);
+            }
+            below = upto;
+        }
+        Some(self.diameter() as f64)
+    }
+
+    //Distance within which 90% of the connected pairs lie
+    pub fn effective_diameter(&self) -> Option<f64> {
+        self.quantile(0.9)
+    }
+
+    //Averages and efficiency over the pairs covered by the distribution
+    pub fn path_stats(&self) -> PathStats {
+        let mut length = 0;
+        let mut inverse = 0.0;
+        for (d, &count) in self.histogram.iter().enumerate().skip(1) {
+            length += d as u64 * count;
+            inverse += count as f64 / d as f64;
+        }
+        let pairs = self.pairs();
+        let connected_pairs = self.connected_pairs();
+        let efficiency = if pairs == 0 { 0.0 } else { inverse / pairs as f64 };
+        PathStats {
+            nodes: self.nodes,
+            pairs,
+            connected_pairs,
+            average: (connected_pairs > 0).then(|| length as f64 / connected_pairs as f64),
+            unreachable_fraction: if pairs == 0 { 0.0 } else { 1.0 - connected_pairs as f64 / pairs as f64 },
+            efficiency,
+            harmonic_distance: (efficiency > 0.0).then(|| 1.0 / efficiency),
+        }
+    }
+}
+
+//Adds the counts of b into a, growing a as needed
+fn add_histograms(mut a: Vec<u64>, b: Vec<u64>) -> Vec<u64> {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0);
+    }
+    for (d, count) in b.into_iter().enumerate() {
+        a[d] += count;
+    }
+    a
+}
+
+//Runs BFS from every source, each of which can reach n - 1 other vertices
+//Counts are integers, so the parallel sum does not depend on the scheduling
+pub(crate) fn distances_from<G: ReviewGraph + ?Sized>(graph: &G, sources: Vec<NodeId>, n: usize) -> DistanceDistribution {
+    let size = graph.node_count();
+    let per_source: Vec<(u32, Vec<u64>)> = sources
+        .par_iter()
+        .map_init(
+            || (vec![UNREACHABLE; size], Vec::new()),
+            |(distances, order), &source| {
+                bfs_visit(graph, source, distances, order);
+                let eccentricity = order.last().map_or(0, |&last| distances[last as usize]);
+                let mut histogram = vec![0u64; eccentricity as usize + 1];
+                for &visited in order.iter() {
+                    histogram[distances[visited as usize] as usize] += 1;
+                    distances[visited as usize] = UNREACHABLE;
+                }
+                histogram[0] = 0;
+                (eccentricity, histogram)
+            },
+        )
+        .collect();
+
+    let mut histogram = vec![0];
+    let mut eccentricities = Vec::with_capacity(per_source.len());
+    for (eccentricity, counts) in per_source {
+        eccentricities.push(eccentricity);
+        histogram = add_histograms(histogram, counts);
+    }
+    DistanceDistribution {
+        nodes: n,
+        histogram,
+        sources,
+        eccentricities,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn test_distance_distribution() {
+        //A path A - B - C - D and a separate edge E - F
+        let edges = vec![
+            ("A".to_string(), "B".to_string()),
+            ("C".to_string(), "B".to_string()),
+            ("C".to_string(), "D".to_string()),
+            ("E".to_string(), "F".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let distribution = graph.distance_distribution();
+        assert_eq!(distribution.histogram, vec![0, 8, 4, 2]);
+        assert_eq!(distribution.diameter(), 3);
+        assert_eq!(distribution.radius(), 1);
+        assert_eq!(distribution.eccentricities, vec![3, 2, 2, 3, 1, 1]);
+        assert_eq!(distribution.share_within(1), Some(8.0 / 14.0));
+        assert_eq!(distribution.share_within(6), Some(1.0));
+        //Every connected pair is within 6 hops but 16 of the 30 ordered pairs are unreachable
+        assert_eq!(distribution.share_of_pairs_within(6), Some(14.0 / 30.0));
+        assert!(!distribution.six_degrees());
+        //90% of 14 pairs is 12.6, which falls 0.6 / 2 of the way through the pairs at distance 3
+        assert!((distribution.effective_diameter().unwrap() - 2.3).abs() < 1e-12);
+
+        let largest = graph.distance_distribution_largest_component();
+        assert_eq!(largest.histogram, vec![0, 6, 4, 2]);
+        assert_eq!(largest.radius(), 2);
+        assert_eq!(largest.share_of_pairs_within(2), Some(10.0 / 12.0));
+        assert!(largest.six_degrees());
+    }
+
+    #[test]
+    fn test_distance_distribution_without_edges() {
+        let graph = Graph::create_undirected(&[]);
+        let distribution = graph.distance_distribution();
+        assert_eq!(distribution.diameter(), 0);
+        assert_eq!(distribution.effective_diameter(), None);
+        assert_eq!(distribution.share_within(6), None);
+        assert_eq!(distribution.share_of_pairs_within(6), None);
+        assert!(!distribution.six_degrees());
+    }
+}
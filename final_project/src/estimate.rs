@@ -0,0 +1,113 @@
+//Approximate average shortest path from a random subset of BFS sources
+//Exact all-pairs BFS is infeasible on the full dumps, a few thousand sources are enough
+//to put a confidence interval around the average
+use crate::graph::{source_totals, NodeId, ReviewGraph, SourceTotals};
+use crate::stats::{mean, percentile, Z_95};
+use rand::prelude::*;
+use serde::Serialize;
+
+//Estimated average shortest path with a 95% confidence interval
+//The interval comes from the bootstrap when bootstrap resamples were drawn,
+//otherwise from the normal approximation mean +- 1.96 standard errors
+#[derive(Debug, Clone, Serialize)]
+pub struct PathEstimate {
+    pub sources: usize,
+    pub mean: f64,
+    pub standard_error: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub bootstrap: usize,
+}
+
+//Ratio of summed lengths to summed pair counts over the chosen sources
+fn ratio(totals: &[SourceTotals], picks: impl Iterator<Item = usize>) -> f64 {
+    let (length, paths) = picks.fold((0, 0), |acc, i| (acc.0 + totals[i].length, acc.1 + totals[i].reached));
+    length as f64 / paths as f64
+}
+
+//Runs BFS from `sources` distinct random vertices and estimates the average shortest path
+//The estimate weighs every reachable pair equally, like average_shortpath, so it is
+//the ratio of summed distances to summed pair counts over the chosen sources
+//Its standard error uses the delta method for ratio estimators
+pub(crate) fn estimate_average_shortpath<G: ReviewGraph + ?Sized, R: Rng>(
+    graph: &G,
+    sources: usize,
+    bootstrap: usize,
+    rng: &mut R,
+) -> PathEstimate {
+    let all: Vec<NodeId> = graph.nodes().collect();
+    estimate_from(graph, &all, sources, bootstrap, rng)
+}
+
+//Same as estimate_average_shortpath with the sources drawn from candidates only
+//Sources inside the largest component only reach its vertices, so drawing them from its
+//members estimates the average path of the largest component
+pub(crate) fn estimate_from<G: ReviewGraph + ?Sized, R: Rng>(
+    graph: &G,
+    candidates: &[NodeId],
+    sources: usize,
+    bootstrap: usize,
+    rng: &mut R,
+) -> PathEstimate {
+    let chosen: Vec<NodeId> = candidates.choose_multiple(rng, sources).copied().collect();
+    let totals = source_totals(graph, &chosen);
+    let k = totals.len();
+    let estimate = ratio(&totals, 0..k);
+
+    let mean_paths = mean(&totals.iter().map(|t| t.reached as f64).collect::<Vec<f64>>());
+    let residuals: Vec<f64> = totals
+        .iter()
+        .map(|t| (t.length as f64 - estimate * t.reached as f64) / mean_paths)
+        .collect();
+    let standard_error = if k < 2 {
+        f64::NAN
+    } else {
+        (residuals.iter().map(|r| r * r).sum::<f64>() / (k * (k - 1)) as f64).sqrt()
+    };
+
+    let (ci_low, ci_high) = if bootstrap > 0 && k > 0 {
+        let mut resamples: Vec<f64> = (0..bootstrap)
+            .map(|_| ratio(&totals, (0..k).map(|_| rng.gen_range(0..k))))
+            .filter(|r| !r.is_nan())
+            .collect();
+        resamples.sort_by(f64::total_cmp);
+        (percentile(&resamples, 0.025), percentile(&resamples, 0.975))
+    } else {
+        (estimate - Z_95 * standard_error, estimate + Z_95 * standard_error)
+    };
+
+    PathEstimate {
+        sources: k,
+        mean: estimate,
+        standard_error,
+        ci_low,
+        ci_high,
+        bootstrap,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::two_game_graph;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_estimate_with_every_source_is_exact() {
+        let graph = two_game_graph(60, 9, 13);
+        let mut rng = StdRng::seed_from_u64(1);
+        let estimate = graph.estimate_average_shortpath(graph.node_count(), 0, &mut rng);
+        assert_eq!(estimate.sources, graph.node_count());
+        assert!((estimate.mean - graph.average_shortpath().average.unwrap()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_estimate_interval_covers_mean() {
+        let graph = two_game_graph(60, 9, 13);
+        let mut rng = StdRng::seed_from_u64(2);
+        let estimate = graph.estimate_average_shortpath(20, 200, &mut rng);
+        assert_eq!(estimate.sources, 20);
+        assert!(estimate.standard_error > 0.0);
+        assert!(estimate.ci_low <= estimate.mean && estimate.mean <= estimate.ci_high);
+    }
+}
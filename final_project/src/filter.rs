@@ -0,0 +1,267 @@
+//Filtered views of the review graph
+//Keeping only the well-rated, verified or dated reviews shows whether fans form tighter
+//communities than the general population
+use crate::graph::{EdgeAttributes, Graph, GraphBuilder, NodeId, NodeKind, ReviewGraph, UNREACHABLE};
+
+//Which reviews a filtered view keeps, every condition that is set must hold
+//Rating bounds drop edges without a rating and date bounds drop edges without a time
+//since is inclusive and until exclusive, both in seconds since the Unix epoch
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeFilter {
+    pub min_rating: Option<f32>,
+    pub max_rating: Option<f32>,
+    pub verified_only: bool,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl EdgeFilter {
+    //True when the filter keeps every edge
+    pub fn is_empty(&self) -> bool {
+        *self == EdgeFilter::default()
+    }
+
+    pub fn keeps(&self, attributes: &EdgeAttributes) -> bool {
+        let within = |value: Option<f32>, bound: Option<f32>, ok: fn(f32, f32) -> bool| match bound {
+            None => true,
+            Some(bound) => value.is_some_and(|v| ok(v, bound)),
+        };
+        within(attributes.rating, self.min_rating, |v, b| v >= b)
+            && within(attributes.rating, self.max_rating, |v, b| v <= b)
+            && (!self.verified_only || attributes.verified)
+            && self.since.is_none_or(|since| attributes.time.is_some_and(|t| t >= since))
+            && self.until.is_none_or(|until| attributes.time.is_some_and(|t| t < until))
+    }
+}
+
+//The reviews a filter keeps and the reviewers and games they connect, over a borrowed graph
+//Nothing of the graph is copied: an edge mask says which reviews are kept and the vertices with
+//a kept review are renumbered 0, 1, ... in the order of their NodeIds in the graph.
+//Vertices left without a kept review are dropped so they do not count as isolated vertices
+//in paths, components or degrees
+#[derive(Debug, Clone)]
+pub struct GraphView<'a> {
+    graph: &'a Graph,
+    //Whether each edge of graph is kept, indexed by edge id
+    kept: Vec<bool>,
+    //Vertex of graph behind every vertex of the view
+    nodes: Vec<NodeId>,
+    //Vertex of the view of every vertex of graph, UNREACHABLE for the dropped ones
+    index: Vec<NodeId>,
+    //Number of kept edges of every vertex of the view
+    degrees: Vec<usize>,
+    edges: usize,
+    reviewers: usize,
+}
+
+impl<'a> GraphView<'a> {
+    //Lays the view out for an edge mask of graph
+    fn new(graph: &'a Graph, kept: Vec<bool>) -> GraphView<'a> {
+        let mut nodes = Vec::new();
+        let mut index = vec![UNREACHABLE; graph.node_count()];
+        let mut degrees = Vec::new();
+        for u in graph.nodes() {
+            let degree = graph.edge_ids(u).iter().filter(|&&e| kept[e as usize]).count();
+            if degree > 0 {
+                index[u as usize] = nodes.len() as NodeId;
+                nodes.push(u);
+                degrees.push(degree);
+            }
+        }
+        let edges = degrees.iter().sum::<usize>() / 2;
+        let reviewers = nodes.iter().filter(|&&u| graph.kind(u) == NodeKind::Reviewer).count();
+        GraphView { graph, kept, nodes, index, degrees, edges, reviewers }
+    }
+
+    //The view of the reviews both this view and the filter keep
+    pub fn filtered(&self, filter: &EdgeFilter) -> GraphView<'a> {
+        let attributes = self.graph.edge_attribute_list();
+        let kept = self.kept.iter().zip(attributes).map(|(&kept, a)| kept && filter.keeps(a)).collect();
+        GraphView::new(self.graph, kept)
+    }
+
+    //Attributes of the kept edge between u and v, None if they are not adjacent in the view
+    pub fn edge_attributes(&self, u: NodeId, v: NodeId) -> Option<&'a EdgeAttributes> {
+        let (u, v) = (self.nodes[u as usize], self.nodes[v as usize]);
+        let i = self.graph.neighbors(u).binary_search(&v).ok()?;
+        let e = self.graph.edge_ids(u)[i] as usize;
+        self.kept[e].then(|| &self.graph.edge_attribute_list()[e])
+    }
+
+    //Copy of the view as a graph of its own, for the analyses that need one
+    //Vertices keep the NodeIds of the view
+    pub fn to_graph(&self) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for &u in &self.nodes {
+            builder.add_node(self.graph.kind(u), self.graph.name(u));
+        }
+        let attributes = self.graph.edge_attribute_list();
+        for (x, &u) in self.nodes.iter().enumerate() {
+            for (&v, &e) in self.graph.neighbors(u).iter().zip(self.graph.edge_ids(u)) {
+                if u < v && self.kept[e as usize] {
+                    builder.add_edge_with(x as NodeId, self.index[v as usize], attributes[e as usize]);
+                }
+            }
+        }
+        builder.build()
+    }
+}
+
+impl ReviewGraph for GraphView<'_> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    fn reviewer_count(&self) -> usize {
+        self.reviewers
+    }
+
+    fn product_count(&self) -> usize {
+        self.nodes.len() - self.reviewers
+    }
+
+    fn kind(&self, u: NodeId) -> NodeKind {
+        self.graph.kind(self.nodes[u as usize])
+    }
+
+    fn name(&self, u: NodeId) -> &str {
+        self.graph.name(self.nodes[u as usize])
+    }
+
+    fn node_id(&self, kind: NodeKind, name: &str) -> Option<NodeId> {
+        let u = self.graph.node_id(kind, name)?;
+        Some(self.index[u as usize]).filter(|&x| x != UNREACHABLE)
+    }
+
+    fn degree(&self, u: NodeId) -> usize {
+        self.degrees[u as usize]
+    }
+
+    fn adjacent(&self, u: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let u = self.nodes[u as usize];
+        self.graph
+            .neighbors(u)
+            .iter()
+            .zip(self.graph.edge_ids(u))
+            .filter(|&(_, &e)| self.kept[e as usize])
+            .map(|(&v, _)| self.index[v as usize])
+    }
+}
+
+impl Graph {
+    //View of the reviews the filter keeps and the reviewers and games they connect
+    //Only the edge mask and the renumbering are allocated, see GraphView
+    pub fn filtered(&self, filter: &EdgeFilter) -> GraphView<'_> {
+        let kept = self.edge_attribute_list().iter().map(|a| filter.keeps(a)).collect();
+        GraphView::new(self, kept)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::reader::Review;
+
+    fn review(reviewer: &str, asin: &str, overall: f32, time: i64, verified: bool) -> Review {
+        Review {
+            reviewer_id: reviewer.to_string(),
+            asin: asin.to_string(),
+            overall: Some(overall),
+            unix_review_time: Some(time),
+            verified: Some(verified),
+            ..Default::default()
+        }
+    }
+
+    //A and B love G1, C panned it, and D reviewed G2 without a rating
+    fn rated_graph() -> Graph {
+        let mut reviews = vec![
+            review("A", "G1", 5.0, 100, true),
+            review("B", "G1", 4.0, 200, false),
+            review("C", "G1", 1.0, 300, true),
+        ];
+        reviews.push(Review { reviewer_id: "D".to_string(), asin: "G2".to_string(), ..Default::default() });
+        Graph::from_reviews(reviews)
+    }
+
+    //Names of the reviewers of G1 in a filtered view
+    fn reviewers_of_g1(view: &GraphView) -> Vec<String> {
+        let g1 = view.node_id(NodeKind::Product, "G1").unwrap();
+        view.adjacent(g1).map(|u| view.name(u).to_string()).collect()
+    }
+
+    #[test]
+    fn test_filtered_graphs() {
+        let graph = rated_graph();
+
+        let positive = graph.filtered(&EdgeFilter { min_rating: Some(4.0), ..Default::default() });
+        assert_eq!(reviewers_of_g1(&positive), vec!["A", "B"]);
+        assert_eq!(positive.edge_count(), 2);
+        //C, D and G2 have no kept review and are dropped instead of counting as isolated vertices
+        assert_eq!(positive.node_count(), 3);
+        assert!(positive.node_id(NodeKind::Reviewer, "C").is_none());
+        assert_eq!(positive.components().count(), 1);
+        let paths = positive.average_shortpath();
+        assert_eq!(paths.pairs, 6);
+        assert_eq!(paths.unreachable_fraction, 0.0);
+        assert_eq!(positive.degree_stats(NodeKind::Reviewer).mean, 1.0);
+
+        let negative = graph.filtered(&EdgeFilter { max_rating: Some(2.0), ..Default::default() });
+        assert_eq!(reviewers_of_g1(&negative), vec!["C"]);
+
+        let verified = graph.filtered(&EdgeFilter { verified_only: true, ..Default::default() });
+        assert_eq!(reviewers_of_g1(&verified), vec!["A", "C"]);
+
+        let dated = graph.filtered(&EdgeFilter { since: Some(150), until: Some(300), ..Default::default() });
+        assert_eq!(reviewers_of_g1(&dated), vec!["B"]);
+        let b = dated.node_id(NodeKind::Reviewer, "B").unwrap();
+        let g1 = dated.node_id(NodeKind::Product, "G1").unwrap();
+        assert_eq!(dated.edge_attributes(b, g1).unwrap().rating, Some(4.0));
+
+        //Filters of filtered views narrow further, and the empty filter keeps everything
+        let both = positive.filtered(&EdgeFilter { verified_only: true, ..Default::default() });
+        assert_eq!(reviewers_of_g1(&both), vec!["A"]);
+        assert!(EdgeFilter::default().is_empty());
+        let everything = graph.filtered(&EdgeFilter::default());
+        assert_eq!((everything.node_count(), everything.edge_count()), (graph.node_count(), graph.edge_count()));
+    }
+
+    #[test]
+    fn test_view_matches_its_copy() {
+        //Paths, components and degrees are the same on the view and on a graph copied from it
+        let reviews: Vec<Review> = (0..60)
+            .flat_map(|i| {
+                let reviewer = format!("R{}", i);
+                [
+                    review(&reviewer, &format!("G{}", i % 7), (i % 5 + 1) as f32, i, i % 2 == 0),
+                    review(&reviewer, &format!("H{}", i % 11), ((i + 2) % 5 + 1) as f32, i, true),
+                ]
+            })
+            .collect();
+        let graph = Graph::from_reviews(reviews);
+        let view = graph.filtered(&EdgeFilter { min_rating: Some(4.0), ..Default::default() });
+        let copy = view.to_graph();
+        assert!(view.node_count() < graph.node_count());
+        assert_eq!((view.node_count(), view.edge_count()), (copy.node_count(), copy.edge_count()));
+        assert_eq!(view.reviewer_count(), copy.reviewer_count());
+        assert!(view.nodes().all(|u| view.name(u) == copy.name(u) && view.adjacent(u).eq(copy.neighbors(u).iter().copied())));
+        assert_eq!(view.average_shortpath(), copy.average_shortpath());
+        assert_eq!(view.components().sizes(), copy.components().sizes());
+        assert_eq!(view.degree_stats(NodeKind::Product).histogram, copy.degree_stats(NodeKind::Product).histogram);
+    }
+
+    #[test]
+    fn test_filter_edges_keeps_node_ids() {
+        let graph = rated_graph();
+        let kept = graph.filter_edges(|attributes| attributes.rating.is_some_and(|r| r >= 4.0));
+        assert_eq!(kept.node_count(), graph.node_count());
+        assert_eq!(kept.edge_count(), 2);
+        assert!(graph.nodes().all(|u| kept.name(u) == graph.name(u)));
+        let c = graph.node_id(NodeKind::Reviewer, "C").unwrap();
+        assert_eq!(kept.degree(c), 0);
+    }
+}
@@ -0,0 +1,935 @@
+//Undirected graph of reviewers and the games they reviewed
+use crate::components::{label_components, Components};
+use crate::degrees::DegreeStats;
+use crate::distances::{distances_from, DistanceDistribution, PathStats};
+use crate::estimate::{estimate_average_shortpath, PathEstimate};
+use crate::reader::{Review, ReviewEdge};
+use clap::ValueEnum;
+use rand::Rng;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+//Dense index of a vertex in a Graph
+pub type NodeId = u32;
+
+//Distance reported by bfs_shortpath for vertices that cannot be reached
+pub const UNREACHABLE: u32 = u32::MAX;
+
+//Which side of the bipartite review graph a vertex is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    Reviewer,
+    Product,
+}
+
+impl NodeKind {
+    //The kind found at the other end of a review edge
+    pub fn other(self) -> NodeKind {
+        match self {
+            NodeKind::Reviewer => NodeKind::Product,
+            NodeKind::Product => NodeKind::Reviewer,
+        }
+    }
+}
+
+//Maps reviewer IDs and ASINs to dense NodeIds and back
+//Reviewers and products have separate keyspaces so equal strings on both sides stay distinct
+//Each name is allocated once and shared between its keyspace and names
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    reviewers: HashMap<Arc<str>, NodeId>,
+    products: HashMap<Arc<str>, NodeId>,
+    names: Vec<Arc<str>>,
+    kinds: Vec<NodeKind>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    fn keyspace(&self, kind: NodeKind) -> &HashMap<Arc<str>, NodeId> {
+        match kind {
+            NodeKind::Reviewer => &self.reviewers,
+            NodeKind::Product => &self.products,
+        }
+    }
+
+    //Returns the id of name, assigning the next free id the first time it is seen
+    pub fn intern(&mut self, kind: NodeKind, name: &str) -> NodeId {
+        if let Some(id) = self.get(kind, name) {
+            return id;
+        }
+        let id = NodeId::try_from(self.names.len()).expect("More than u32::MAX vertices");
+        let ids = match kind {
+            NodeKind::Reviewer => &mut self.reviewers,
+            NodeKind::Product => &mut self.products,
+        };
+        let name: Arc<str> = Arc::from(name);
+        ids.insert(Arc::clone(&name), id);
+        self.names.push(name);
+        self.kinds.push(kind);
+        id
+    }
+
+    pub fn get(&self, kind: NodeKind, name: &str) -> Option<NodeId> {
+        self.keyspace(kind).get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id as usize]
+    }
+
+    pub fn kind(&self, id: NodeId) -> NodeKind {
+        self.kinds[id as usize]
+    }
+
+    //Number of interned names of one kind
+    pub fn count(&self, kind: NodeKind) -> usize {
+        self.keyspace(kind).len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+//What is known about the reviews behind one edge
+//A reviewer who reviewed the same game more than once gets one edge for all of those reviews:
+//reviews counts them, rating is their mean, time the earliest, votes the total,
+//and verified is set when any of them was a verified purchase
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EdgeAttributes {
+    pub reviews: u32,
+    pub rating: Option<f32>,
+    pub time: Option<i64>,
+    pub votes: u32,
+    pub verified: bool,
+}
+
+//An edge added without review metadata stands for a single review
+impl Default for EdgeAttributes {
+    fn default() -> EdgeAttributes {
+        EdgeAttributes {
+            reviews: 1,
+            rating: None,
+            time: None,
+            votes: 0,
+            verified: false,
+        }
+    }
+}
+
+impl EdgeAttributes {
+    pub fn from_review(review: &ReviewEdge) -> EdgeAttributes {
+        EdgeAttributes {
+            reviews: 1,
+            rating: review.overall,
+            time: review.unix_review_time,
+            votes: review.vote.unwrap_or(0),
+            verified: review.verified.unwrap_or(false),
+        }
+    }
+
+    //Combines the attributes of repeated reviews of the same game by the same reviewer
+    //The mean rating weighs every rated edge by its number of reviews
+    fn merge(group: &[EdgeAttributes]) -> EdgeAttributes {
+        let rated: Vec<&EdgeAttributes> = group.iter().filter(|a| a.rating.is_some()).collect();
+        let rated_reviews: u32 = rated.iter().map(|a| a.reviews).sum();
+        let rating_sum: f32 = rated.iter().map(|a| a.rating.unwrap() * a.reviews as f32).sum();
+        EdgeAttributes {
+            reviews: group.iter().map(|a| a.reviews).sum(),
+            rating: (rated_reviews > 0).then(|| rating_sum / rated_reviews as f32),
+            time: group.iter().filter_map(|a| a.time).min(),
+            votes: group.iter().map(|a| a.votes).sum(),
+            verified: group.iter().any(|a| a.verified),
+        }
+    }
+}
+
+//Creatign a "Graph" struct
+//To review the record with a reviewer ID and ASIN
+//Every vertex is either a reviewer or a product, reviews only connect the two sides
+//Represents an undirected graph in compressed sparse row form:
+//the neighbours of vertex u are targets[offsets[u]..offsets[u + 1]], sorted and without duplicates
+//edge_ids runs parallel to targets, so both copies of an undirected edge find its attributes
+//Names and attributes are shared with the copies made by filter_edges
+#[derive(Debug, Default)]
+pub struct Graph {
+    interner: Arc<Interner>,
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    edge_ids: Vec<u32>,
+    attributes: Arc<Vec<EdgeAttributes>>,
+}
+
+//Define methods for the Graph struct
+impl Graph {
+
+    //Creates and returns a new empty graph
+    pub fn new() -> Graph {
+        Graph {
+            interner: Arc::new(Interner::new()),
+            offsets: vec![0],
+            targets: Vec::new(),
+            edge_ids: Vec::new(),
+            attributes: Arc::new(Vec::new()),
+        }
+    }
+
+    //Number of vertices in the graph
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    //Number of undirected edges, each edge is stored once per endpoint
+    pub fn edge_count(&self) -> usize {
+        self.targets.len() / 2
+    }
+
+    //All vertex ids, 0 to node_count() - 1
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.node_count() as NodeId
+    }
+
+    pub fn neighbors(&self, u: NodeId) -> &[NodeId] {
+        let u = u as usize;
+        &self.targets[self.offsets[u]..self.offsets[u + 1]]
+    }
+
+    //Neighbours of u with the attributes of the edge to each of them, in neighbour order
+    pub fn neighbors_with_attributes(&self, u: NodeId) -> impl Iterator<Item = (NodeId, &EdgeAttributes)> + '_ {
+        let range = self.offsets[u as usize]..self.offsets[u as usize + 1];
+        self.targets[range.clone()]
+            .iter()
+            .zip(&self.edge_ids[range])
+            .map(|(&v, &e)| (v, &self.attributes[e as usize]))
+    }
+
+    //Ids of the edges to the neighbours of u, parallel to neighbors(u)
+    //Both copies of an undirected edge have the same id, which indexes edge_attribute_list
+    pub(crate) fn edge_ids(&self, u: NodeId) -> &[u32] {
+        let u = u as usize;
+        &self.edge_ids[self.offsets[u]..self.offsets[u + 1]]
+    }
+
+    //Attributes of every edge, indexed by edge id
+    pub(crate) fn edge_attribute_list(&self) -> &[EdgeAttributes] {
+        &self.attributes
+    }
+
+    //Attributes of the edge between u and v, None if they are not adjacent
+    pub fn edge_attributes(&self, u: NodeId, v: NodeId) -> Option<&EdgeAttributes> {
+        let i = self.neighbors(u).binary_search(&v).ok()?;
+        Some(&self.attributes[self.edge_ids[self.offsets[u as usize] + i] as usize])
+    }
+
+    //Every edge once as (u, v, attributes) with u < v
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &EdgeAttributes)> + '_ {
+        self.nodes().flat_map(move |u| {
+            self.neighbors_with_attributes(u)
+                .filter(move |&(v, _)| u < v)
+                .map(move |(v, attributes)| (u, v, attributes))
+        })
+    }
+
+    pub fn degree(&self, u: NodeId) -> usize {
+        self.neighbors(u).len()
+    }
+
+    //Looks up the vertex of a reviewer ID or ASIN
+    pub fn node_id(&self, kind: NodeKind, name: &str) -> Option<NodeId> {
+        self.interner.get(kind, name)
+    }
+
+    //The reviewer ID or ASIN of a vertex
+    pub fn name(&self, u: NodeId) -> &str {
+        self.interner.name(u)
+    }
+
+    pub fn kind(&self, u: NodeId) -> NodeKind {
+        self.interner.kind(u)
+    }
+
+    //Vertices on one side of the graph
+    pub fn nodes_of(&self, kind: NodeKind) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes().filter(move |&u| self.kind(u) == kind)
+    }
+
+    pub fn reviewers(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes_of(NodeKind::Reviewer)
+    }
+
+    pub fn products(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes_of(NodeKind::Product)
+    }
+
+    pub fn reviewer_count(&self) -> usize {
+        self.interner.count(NodeKind::Reviewer)
+    }
+
+    pub fn product_count(&self) -> usize {
+        self.interner.count(NodeKind::Product)
+    }
+
+    //Degrees of the vertices on one side, in NodeId order
+    pub fn degrees(&self, kind: NodeKind) -> Vec<usize> {
+        self.nodes_of(kind).map(|u| self.degree(u)).collect()
+    }
+
+    //Checks that every edge joins a reviewer to a product
+    pub fn is_bipartite(&self) -> bool {
+        self.nodes()
+            .all(|u| self.neighbors(u).iter().all(|&v| self.kind(v) != self.kind(u)))
+    }
+
+    //Vertices two hops away on the same side, e.g. reviewer -> game -> reviewer
+    //Sorted and without u itself
+    pub fn two_hop_neighbors(&self, u: NodeId) -> Vec<NodeId> {
+        let mut result: Vec<NodeId> = self
+            .neighbors(u)
+            .iter()
+            .flat_map(|&via| self.neighbors(via).iter().copied())
+            .filter(|&w| w != u)
+            .collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    //The subgraph made of the given vertices and every edge between them
+    //Vertices keep their names and kinds but are renumbered in the order given
+    pub fn induced_subgraph(&self, nodes: &[NodeId]) -> Graph {
+        let mut builder = GraphBuilder::new();
+        let mut mapping = vec![UNREACHABLE; self.node_count()];
+        for &u in nodes {
+            mapping[u as usize] = builder.add_node(self.kind(u), self.name(u));
+        }
+        for &u in nodes {
+            for (v, &attributes) in self.neighbors_with_attributes(u) {
+                if u < v && mapping[v as usize] != UNREACHABLE {
+                    builder.add_edge_with(mapping[u as usize], mapping[v as usize], attributes);
+                }
+            }
+        }
+        builder.build()
+    }
+
+    //The subgraph made of the given edges and their endpoints only
+    //Vertices keep their names and kinds but are renumbered in order of first appearance
+    //Each edge must be listed once in either direction, a repeated edge merges as a repeated review
+    pub fn edge_subgraph(&self, edges: &[(NodeId, NodeId)]) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for &(u, v) in edges {
+            let attributes = *self.edge_attributes(u, v).expect("edge_subgraph is given edges of the graph");
+            let u = builder.add_node(self.kind(u), self.name(u));
+            let v = builder.add_node(self.kind(v), self.name(v));
+            builder.add_edge_with(u, v, attributes);
+        }
+        builder.build()
+    }
+
+    //Copy of the graph with only the edges whose attributes pass keep
+    //offsets, targets and edge_ids are rebuilt on every call, only the names and edge attributes
+    //are shared with self. Every vertex keeps its NodeId, including those left without edges,
+    //see Graph::filtered for a view of the kept edges and their endpoints only
+    pub fn filter_edges<F: Fn(&EdgeAttributes) -> bool>(&self, keep: F) -> Graph {
+        let mut offsets = Vec::with_capacity(self.offsets.len());
+        let mut targets = Vec::new();
+        let mut edge_ids = Vec::new();
+        offsets.push(0);
+        for u in 0..self.node_count() {
+            for i in self.offsets[u]..self.offsets[u + 1] {
+                if keep(&self.attributes[self.edge_ids[i] as usize]) {
+                    targets.push(self.targets[i]);
+                    edge_ids.push(self.edge_ids[i]);
+                }
+            }
+            offsets.push(targets.len());
+        }
+        Graph {
+            interner: Arc::clone(&self.interner),
+            offsets,
+            targets,
+            edge_ids,
+            attributes: Arc::clone(&self.attributes),
+        }
+    }
+
+    //Creates a graph from a stream of reviews without collecting them first
+    pub fn from_reviews<I: IntoIterator<Item = Review>>(reviews: I) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for review in reviews {
+            builder.add_review(&review.edge());
+        }
+        builder.build()
+    }
+
+    //Creates undriected graph from a list of edges
+    //Each tuple is a reviewer ID and the ASIN they reviewed
+    //Iterates over each tuple and adds each edge to the graph
+    pub fn create_undirected(edges: &[(String, String)]) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for (u, v) in edges {
+            builder.add_edge(u, v);
+        }
+        builder.build()
+    }
+
+    //Breadth-first search to calculate shortest path from start node
+    //Returns the distance to every vertex indexed by NodeId, UNREACHABLE if there is no path
+    pub fn bfs_shortpath(&self, start: NodeId) -> Vec<u32> {
+        self.bfs_shortpath_multi(&[start], None)
+    }
+
+    //Distance from the nearest of several start vertices, e.g. every reviewer of one game
+    //With max_depth, vertices further than max_depth hops are left UNREACHABLE
+    pub fn bfs_shortpath_multi(&self, starts: &[NodeId], max_depth: Option<u32>) -> Vec<u32> {
+        let mut distances = vec![UNREACHABLE; self.node_count()];
+        let mut order = Vec::new();
+        bfs_visit_limited(self, starts, max_depth.unwrap_or(UNREACHABLE), &mut distances, &mut order);
+        distances
+    }
+
+    //The k-hop neighbourhood of a seed set with the distance of every vertex in it,
+    //in BFS order starting with the seeds themselves
+    pub fn neighborhood(&self, seeds: &[NodeId], k: u32) -> Vec<(NodeId, u32)> {
+        let mut distances = vec![UNREACHABLE; self.node_count()];
+        let mut order = Vec::new();
+        bfs_visit_limited(self, seeds, k, &mut distances, &mut order);
+        order.into_iter().map(|u| (u, distances[u as usize])).collect()
+    }
+
+    //Number of ordered pairs of distinct vertices at most max_depth hops apart
+    //Every search stops at max_depth, so checking six degrees never explores further than 6 hops
+    pub fn pairs_within(&self, max_depth: u32) -> u64 {
+        let n = self.node_count();
+        self.nodes()
+            .into_par_iter()
+            .map_init(
+                || (vec![UNREACHABLE; n], Vec::new()),
+                |(distances, order), source| {
+                    bfs_visit_limited(self, &[source], max_depth, distances, order);
+                    for &visited in order.iter() {
+                        distances[visited as usize] = UNREACHABLE;
+                    }
+                    order.len() as u64 - 1
+                },
+            )
+            .sum()
+    }
+}
+
+//Read access to the vertices and edges of a review graph
+//Implemented by Graph and by the GraphView of a filter, so the searches behind paths,
+//components, degrees and the path estimator run on a filtered view without copying the graph
+pub trait ReviewGraph: Sync {
+    fn node_count(&self) -> usize;
+    fn edge_count(&self) -> usize;
+    fn reviewer_count(&self) -> usize;
+    fn product_count(&self) -> usize;
+    fn kind(&self, u: NodeId) -> NodeKind;
+    fn name(&self, u: NodeId) -> &str;
+    fn node_id(&self, kind: NodeKind, name: &str) -> Option<NodeId>;
+    fn degree(&self, u: NodeId) -> usize;
+
+    //Neighbours of u in increasing NodeId order
+    fn adjacent(&self, u: NodeId) -> impl Iterator<Item = NodeId> + '_;
+
+    //All vertex ids, 0 to node_count() - 1
+    fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.node_count() as NodeId
+    }
+
+    //Degrees of the vertices on one side, in NodeId order
+    fn degrees(&self, kind: NodeKind) -> Vec<usize> {
+        self.nodes().filter(|&u| self.kind(u) == kind).map(|u| self.degree(u)).collect()
+    }
+
+    //Connected components, see components.rs
+    fn components(&self) -> Components {
+        label_components(self)
+    }
+
+    //Distances between every ordered pair of vertices
+    fn distance_distribution(&self) -> DistanceDistribution {
+        distances_from(self, self.nodes().collect(), self.node_count())
+    }
+
+    //Distances between the vertices of the largest connected component
+    fn distance_distribution_largest_component(&self) -> DistanceDistribution {
+        let components = self.components();
+        let sources = if components.count() == 0 { Vec::new() } else { components.members(0) };
+        let n = sources.len();
+        distances_from(self, sources, n)
+    }
+
+    //Calculates the average shortest path for the graph
+    //The average only covers connected pairs, unreachable pairs are counted separately
+    //and enter the efficiency with distance infinity
+    fn average_shortpath(&self) -> PathStats {
+        self.distance_distribution().path_stats()
+    }
+
+    //Same as average_shortpath, restricted to the pairs inside the largest connected component
+    fn average_shortpath_largest_component(&self) -> PathStats {
+        self.distance_distribution_largest_component().path_stats()
+    }
+
+    //Degree statistics of the reviewers or of the games
+    fn degree_stats(&self, kind: NodeKind) -> DegreeStats {
+        DegreeStats::new(kind, &self.degrees(kind))
+    }
+
+    //Average shortest path from `sources` random BFS sources, see estimate.rs
+    fn estimate_average_shortpath<R: Rng>(&self, sources: usize, bootstrap: usize, rng: &mut R) -> PathEstimate {
+        estimate_average_shortpath(self, sources, bootstrap, rng)
+    }
+}
+
+impl ReviewGraph for Graph {
+    fn node_count(&self) -> usize {
+        Graph::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        Graph::edge_count(self)
+    }
+
+    fn reviewer_count(&self) -> usize {
+        Graph::reviewer_count(self)
+    }
+
+    fn product_count(&self) -> usize {
+        Graph::product_count(self)
+    }
+
+    fn kind(&self, u: NodeId) -> NodeKind {
+        Graph::kind(self, u)
+    }
+
+    fn name(&self, u: NodeId) -> &str {
+        Graph::name(self, u)
+    }
+
+    fn node_id(&self, kind: NodeKind, name: &str) -> Option<NodeId> {
+        Graph::node_id(self, kind, name)
+    }
+
+    fn degree(&self, u: NodeId) -> usize {
+        Graph::degree(self, u)
+    }
+
+    fn adjacent(&self, u: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.neighbors(u).iter().copied()
+    }
+}
+
+//Breadth-first search from start that records every visited vertex in order
+//distances must hold UNREACHABLE for every vertex on entry
+//Only the vertices listed in order are changed, so callers can reset them cheaply
+pub(crate) fn bfs_visit<G: ReviewGraph + ?Sized>(graph: &G, start: NodeId, distances: &mut [u32], order: &mut Vec<NodeId>) {
+    bfs_visit_limited(graph, &[start], UNREACHABLE, distances, order);
+}
+
+//Same as bfs_visit, starting from every vertex in starts at distance 0
+//and not expanding vertices max_depth hops away
+pub(crate) fn bfs_visit_limited<G: ReviewGraph + ?Sized>(
+    graph: &G,
+    starts: &[NodeId],
+    max_depth: u32,
+    distances: &mut [u32],
+    order: &mut Vec<NodeId>,
+) {
+    order.clear();
+    for &start in starts {
+        if distances[start as usize] == UNREACHABLE {
+            distances[start as usize] = 0;
+            order.push(start);
+        }
+    }
+    let mut head = 0;
+    while head < order.len() {
+        let current = order[head];
+        head += 1;
+        if distances[current as usize] >= max_depth {
+            continue;
+        }
+        let next_distance = distances[current as usize] + 1;
+        for neighbor in graph.adjacent(current) {
+            if distances[neighbor as usize] == UNREACHABLE {
+                distances[neighbor as usize] = next_distance;
+                order.push(neighbor);
+            }
+        }
+    }
+}
+
+//Distance totals of a BFS from each source, in source order
+//Each source is searched on its own thread from the rayon pool, with the distance
+//and queue buffers reused by every search on that thread
+pub(crate) fn source_totals<G: ReviewGraph + ?Sized>(graph: &G, sources: &[NodeId]) -> Vec<SourceTotals> {
+    let n = graph.node_count();
+    sources
+        .par_iter()
+        .map_init(
+            || (vec![UNREACHABLE; n], Vec::new()),
+            |(distances, order), &source| {
+                bfs_visit(graph, source, distances, order);
+                let mut length: u64 = 0;
+                for &visited in order.iter() {
+                    length += distances[visited as usize] as u64;
+                    distances[visited as usize] = UNREACHABLE;
+                }
+                SourceTotals {
+                    length,
+                    reached: order.len() as u64 - 1,
+                }
+            },
+        )
+        .collect()
+}
+
+//Distance totals of one BFS, see source_totals
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SourceTotals {
+    //Sum of the distances to every reached vertex
+    pub length: u64,
+    //Number of reached vertices other than the source
+    pub reached: u64,
+}
+
+//Incrementally builds a Graph from reviews as they are read
+//Only interned ids are kept per edge, the adjacency is laid out once in build
+#[derive(Debug, Default)]
+pub struct GraphBuilder {
+    interner: Interner,
+    edges: Vec<(NodeId, NodeId, EdgeAttributes)>,
+}
+
+impl GraphBuilder {
+    pub fn new() -> GraphBuilder {
+        GraphBuilder::default()
+    }
+
+    //Adds a vertex, or returns the existing one with the same kind and name
+    pub fn add_node(&mut self, kind: NodeKind, name: &str) -> NodeId {
+        self.interner.intern(kind, name)
+    }
+
+    //Adds an undirected edge between two vertices that have already been added
+    pub fn add_edge_ids(&mut self, u: NodeId, v: NodeId) {
+        self.add_edge_with(u, v, EdgeAttributes::default());
+    }
+
+    //Adds an undirected edge with the attributes of its review
+    pub fn add_edge_with(&mut self, u: NodeId, v: NodeId, attributes: EdgeAttributes) {
+        self.edges.push((u, v, attributes));
+    }
+
+    //Adds an undirected edge between a reviewer and a product
+    pub fn add_edge(&mut self, reviewer: &str, product: &str) {
+        let u = self.add_node(NodeKind::Reviewer, reviewer);
+        let v = self.add_node(NodeKind::Product, product);
+        self.add_edge_ids(u, v);
+    }
+
+    //Adds an edge between the reviewer and the game they reviewed, keeping the review's metadata
+    pub fn add_review(&mut self, review: &ReviewEdge) {
+        let u = self.add_node(NodeKind::Reviewer, &review.reviewer_id);
+        let v = self.add_node(NodeKind::Product, &review.asin);
+        self.add_edge_with(u, v, EdgeAttributes::from_review(review));
+    }
+
+    //Lays the edges out in compressed sparse row form
+    //Repeated edges are merged so every neighbour appears once, with their attributes combined
+    pub fn build(self) -> Graph {
+        let n = self.interner.len();
+        let mut offsets = vec![0; n + 1];
+        for &(u, v, _) in &self.edges {
+            offsets[u as usize + 1] += 1;
+            offsets[v as usize + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        //Each slot holds the neighbour and the index of the added edge it came from
+        let mut fill = offsets.clone();
+        let mut slots: Vec<(NodeId, u32)> = vec![(0, 0); offsets[n]];
+        for (e, &(u, v, _)) in self.edges.iter().enumerate() {
+            slots[fill[u as usize]] = (v, e as u32);
+            fill[u as usize] += 1;
+            slots[fill[v as usize]] = (u, e as u32);
+            fill[v as usize] += 1;
+        }
+
+        //Sort each row and compact away the duplicates
+        //An edge gets its id and merged attributes in the row of its smaller endpoint,
+        //the row of the larger endpoint looks the id up there
+        let mut targets = Vec::with_capacity(slots.len());
+        let mut edge_ids: Vec<u32> = Vec::with_capacity(slots.len());
+        let mut attributes = Vec::new();
+        let mut group = Vec::new();
+        for u in 0..n {
+            let (start, end) = (offsets[u], offsets[u + 1]);
+            slots[start..end].sort_unstable();
+            offsets[u] = targets.len();
+            let mut i = start;
+            while i < end {
+                let v = slots[i].0;
+                group.clear();
+                while i < end && slots[i].0 == v {
+                    group.push(self.edges[slots[i].1 as usize].2);
+                    i += 1;
+                }
+                let id = if (u as NodeId) <= v {
+                    attributes.push(EdgeAttributes::merge(&group));
+                    (attributes.len() - 1) as u32
+                } else {
+                    let row = offsets[v as usize]..offsets[v as usize + 1];
+                    let position = targets[row.clone()]
+                        .binary_search(&(u as NodeId))
+                        .expect("rows of smaller vertices are laid out first");
+                    edge_ids[row.start + position]
+                };
+                targets.push(v);
+                edge_ids.push(id);
+            }
+        }
+        offsets[n] = targets.len();
+        targets.shrink_to_fit();
+        edge_ids.shrink_to_fit();
+
+        Graph {
+            interner: Arc::new(self.interner),
+            offsets,
+            targets,
+            edge_ids,
+            attributes: Arc::new(attributes),
+        }
+    }
+}
+
+//Test graph where reviewer R{i} reviewed games G{i % g} and H{i % h} for i below reviewers
+//With coprime g and h it is connected and has many different path lengths
+#[cfg(test)]
+pub(crate) fn two_game_graph(reviewers: usize, g: usize, h: usize) -> Graph {
+    let edges: Vec<(String, String)> = (0..reviewers)
+        .flat_map(|i| vec![(format!("R{}", i), format!("G{}", i % g)), (format!("R{}", i), format!("H{}", i % h))])
+        .collect();
+    Graph::create_undirected(&edges)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bfs_shortpath() {
+        //Reviewers A and C, games B and D, forming the path A - B - C - D
+        let edges = vec! [
+            ("A".to_string(), "B".to_string()),
+            ("C".to_string(), "B".to_string()),
+            ("C".to_string(), "D".to_string()),
+        ];
+
+        let graph = Graph::create_undirected(&edges);
+        let distances = graph.bfs_shortpath(graph.node_id(NodeKind::Reviewer, "A").unwrap());
+        let distance = |kind, name| distances[graph.node_id(kind, name).unwrap() as usize];
+        assert_eq!(distance(NodeKind::Product, "B"), 1);
+        assert_eq!(distance(NodeKind::Reviewer, "C"), 2);
+        assert_eq!(distance(NodeKind::Product, "D"), 3);
+    }
+
+    #[test]
+    fn test_average_shortpath() {
+        let edges = vec![
+            ("A".to_string(), "B".to_string()),
+            ("C".to_string(), "B".to_string()),
+            ("C".to_string(), "D".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let avg_shortpath = graph.average_shortpath();
+        assert_eq!(avg_shortpath.average, Some(20.0 / 12.0));
+        assert_eq!(avg_shortpath.unreachable_fraction, 0.0);
+    }
+
+    #[test]
+    fn test_average_shortpath_disconnected() {
+        //A path A - B - C - D and a separate edge E - F
+        let edges = vec![
+            ("A".to_string(), "B".to_string()),
+            ("C".to_string(), "B".to_string()),
+            ("C".to_string(), "D".to_string()),
+            ("E".to_string(), "F".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let stats = graph.average_shortpath();
+        assert_eq!(stats.pairs, 30);
+        assert_eq!(stats.connected_pairs, 14);
+        assert_eq!(stats.average, Some(22.0 / 14.0));
+        assert_eq!(stats.unreachable_fraction, 1.0 - 14.0 / 30.0);
+        //Path pairs contribute 2 * (3 + 2 * 1/2 + 1/3), the separate edge 2 * 1
+        let inverse = 2.0 * (3.0 + 2.0 * 0.5 + 1.0 / 3.0) + 2.0;
+        assert!((stats.efficiency - inverse / 30.0).abs() < 1e-12);
+
+        let largest = graph.average_shortpath_largest_component();
+        assert_eq!(largest.nodes, 4);
+        assert_eq!(largest.average, Some(20.0 / 12.0));
+        assert_eq!(largest.unreachable_fraction, 0.0);
+    }
+
+    #[test]
+    fn test_average_shortpath_without_edges() {
+        let graph = Graph::create_undirected(&[]);
+        let stats = graph.average_shortpath();
+        assert_eq!(stats.average, None);
+        assert_eq!(stats.efficiency, 0.0);
+        assert_eq!(stats.harmonic_distance, None);
+    }
+
+    #[test]
+    fn test_average_shortpath_matches_sequential() {
+        let graph = two_game_graph(40, 7, 11);
+        let (mut total, mut count) = (0u64, 0u64);
+        for u in graph.nodes() {
+            for d in graph.bfs_shortpath(u) {
+                if d != UNREACHABLE && d > 0 {
+                    total += d as u64;
+                    count += 1;
+                }
+            }
+        }
+        assert_eq!(graph.average_shortpath().average, Some(total as f64 / count as f64));
+    }
+
+    #[test]
+    fn test_depth_limited_multi_source_bfs() {
+        //Games G1 and G2 share reviewer B, C reviewed G2 and G3, D reviewed G3
+        let edges = vec![
+            ("A".to_string(), "G1".to_string()),
+            ("B".to_string(), "G1".to_string()),
+            ("B".to_string(), "G2".to_string()),
+            ("C".to_string(), "G2".to_string()),
+            ("C".to_string(), "G3".to_string()),
+            ("D".to_string(), "G3".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        let id = |kind, name| graph.node_id(kind, name).unwrap();
+        let g1 = id(NodeKind::Product, "G1");
+
+        //Start from every reviewer of G1
+        let reviewers = graph.neighbors(g1).to_vec();
+        let distances = graph.bfs_shortpath_multi(&reviewers, None);
+        assert_eq!(distances[id(NodeKind::Reviewer, "A") as usize], 0);
+        assert_eq!(distances[id(NodeKind::Reviewer, "C") as usize], 2);
+        assert_eq!(distances[id(NodeKind::Reviewer, "D") as usize], 4);
+
+        let limited = graph.bfs_shortpath_multi(&reviewers, Some(2));
+        assert_eq!(limited[id(NodeKind::Reviewer, "C") as usize], 2);
+        assert_eq!(limited[id(NodeKind::Product, "G3") as usize], UNREACHABLE);
+
+        let hood: Vec<&str> = graph.neighborhood(&[g1], 2).iter().map(|&(u, _)| graph.name(u)).collect();
+        assert_eq!(hood, vec!["G1", "A", "B", "G2"]);
+
+        //A path of 7 vertices has 6 + 5 unordered pairs within 2 hops
+        assert_eq!(graph.pairs_within(2), 22);
+        assert_eq!(graph.pairs_within(6), 42);
+    }
+
+    #[test]
+    fn test_from_reviews() {
+        let reviews = vec![
+            Review { reviewer_id: "A1".to_string(), asin: "B1".to_string(), ..Default::default() },
+            Review { reviewer_id: "A1".to_string(), asin: "B2".to_string(), ..Default::default() },
+            Review { reviewer_id: "A1".to_string(), asin: "B1".to_string(), ..Default::default() },
+        ];
+        let graph = Graph::from_reviews(reviews);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        let a1 = graph.node_id(NodeKind::Reviewer, "A1").unwrap();
+        let names: Vec<&str> = graph.neighbors(a1).iter().map(|&v| graph.name(v)).collect();
+        assert_eq!(names, vec!["B1", "B2"]);
+    }
+
+    #[test]
+    fn test_repeated_reviews_merge_attributes() {
+        let review = |asin: &str, overall, time, vote, verified| Review {
+            reviewer_id: "A1".to_string(),
+            asin: asin.to_string(),
+            overall: Some(overall),
+            unix_review_time: Some(time),
+            vote,
+            verified: Some(verified),
+            ..Default::default()
+        };
+        let graph = Graph::from_reviews(vec![
+            review("B1", 5.0, 300, Some(2), false),
+            review("B2", 1.0, 100, None, false),
+            review("B1", 2.0, 200, Some(3), true),
+        ]);
+        let a1 = graph.node_id(NodeKind::Reviewer, "A1").unwrap();
+        let b1 = graph.node_id(NodeKind::Product, "B1").unwrap();
+        let merged = EdgeAttributes { reviews: 2, rating: Some(3.5), time: Some(200), votes: 5, verified: true };
+        assert_eq!(graph.edge_attributes(a1, b1), Some(&merged));
+        assert_eq!(graph.edge_attributes(b1, a1), Some(&merged));
+        assert_eq!(graph.edges().count(), 2);
+
+        //Subgraphs keep the attributes of the edges they copy
+        let sub = graph.induced_subgraph(&[b1, a1]);
+        assert_eq!(sub.edge_attributes(1, 0), Some(&merged));
+        assert!(graph.edge_attributes(b1, b1).is_none());
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        assert_eq!(interner.intern(NodeKind::Reviewer, "A1"), 0);
+        assert_eq!(interner.intern(NodeKind::Product, "B1"), 1);
+        assert_eq!(interner.intern(NodeKind::Reviewer, "A1"), 0);
+        assert_eq!(interner.get(NodeKind::Product, "B1"), Some(1));
+        assert_eq!(interner.get(NodeKind::Reviewer, "B1"), None);
+        assert_eq!(interner.name(1), "B1");
+        assert_eq!(interner.kind(1), NodeKind::Product);
+        assert_eq!(interner.len(), 2);
+        //The keyspace and names share one allocation per name
+        let (key, _) = interner.products.get_key_value("B1").unwrap();
+        assert!(Arc::ptr_eq(key, &interner.names[1]));
+    }
+
+    #[test]
+    fn test_bipartite_sides() {
+        //Reviewer "X" and game "X" are different vertices
+        let edges = vec![
+            ("X".to_string(), "X".to_string()),
+            ("Y".to_string(), "X".to_string()),
+            ("Y".to_string(), "Z".to_string()),
+        ];
+        let graph = Graph::create_undirected(&edges);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.reviewer_count(), 2);
+        assert_eq!(graph.product_count(), 2);
+        assert!(graph.is_bipartite());
+
+        let x = graph.node_id(NodeKind::Reviewer, "X").unwrap();
+        let y = graph.node_id(NodeKind::Reviewer, "Y").unwrap();
+        assert_eq!(graph.two_hop_neighbors(x), vec![y]);
+        assert_eq!(graph.degrees(NodeKind::Reviewer), vec![1, 2]);
+        assert_eq!(graph.degrees(NodeKind::Product), vec![2, 1]);
+        let products: Vec<&str> = graph.products().map(|u| graph.name(u)).collect();
+        assert_eq!(products, vec!["X", "Z"]);
+
+        let mut builder = GraphBuilder::new();
+        let a = builder.add_node(NodeKind::Reviewer, "A");
+        let b = builder.add_node(NodeKind::Reviewer, "B");
+        builder.add_edge_ids(a, b);
+        assert!(!builder.build().is_bipartite());
+    }
+}
@@ -0,0 +1,20 @@
+//Graph analysis of Amazon review dumps
+//The binary in main.rs exposes these modules through the command line
+pub mod reader;
+pub mod graph;
+pub mod sample;
+pub mod projection;
+pub mod stats;
+pub mod estimate;
+pub mod components;
+pub mod distances;
+pub mod paths;
+pub mod community;
+pub mod centrality;
+pub mod degrees;
+pub mod clustering;
+pub mod null_model;
+pub mod weighted;
+pub mod dates;
+pub mod filter;
+pub mod temporal;
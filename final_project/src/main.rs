@@ -1,256 +1,561 @@
 //Importing necessary libraries that will be used 
-use flate2::bufread::GzDecoder;
+mod cli;
+mod report;
+
+use clap::Parser;
+use cli::{Cli, Command, EstimateArgs, GraphArgs};
+use final_project::degrees::DegreeStats;
+use final_project::distances::DistanceDistribution;
+use final_project::filter::GraphView;
+use final_project::graph::{Graph, GraphBuilder, NodeId, NodeKind, ReviewGraph};
+use final_project::reader::{ParseMode, ReadReport, ReviewEdge, ReviewReader, STDIN};
+use final_project::sample::{sample_ids, SampleMethod, SampledIds, UniqueIds};
+use final_project::stats::{permutation_test, summarize, welch_t_test};
+use final_project::temporal::PathSampling;
 use rand::prelude::*;
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet, VecDeque};
+use rand::rngs::StdRng;
+use report::{
+    emit, CentralityReport, CommunitiesReport, CompareReport, ComponentsReport, DegreesReport, DistancesReport,
+    EstimateReport, NeighborhoodNode, NeighborhoodReport, NullModelReport, PathReport, PathsReport, ProjectionEdge, ProjectionReport,
+    SampleEdge, SampleReport, SnapshotsReport, StatsReport, VersusReport, WithinReport,
+};
 use std::fs::File;
-use std::io::{BufReader, BufRead};
+use std::io::{self, BufWriter, Write};
+use std::process;
 use std::time::Instant;
 
-//Creating a struct named "Review" to group data
-//Deserialize tells Serde how to interpret the data
-//Identifiying the traits/fields used for this code
-#[derive(Deserialize, Debug)]
-struct Review {
-    #[serde(rename = "reviewerID")]
-    reviewer_id: String,
-    asin: String,
+//Creates the random number generator for sampling
+//A fixed seed reproduces the same samples, main fills one in for every run that draws random numbers
+fn make_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
 }
 
-//Creatign a "Graph" struct
-//To review the record with a reviewer ID and ASIN
-//Represents an undirected graph using an adjacency list
-#[derive(Debug)]
-struct Graph{
-    outedges: HashMap<String, HashSet<String>>,
+//Summarises the shortest paths of a graph, optionally only inside its largest component
+fn summarise_paths<G: ReviewGraph>(graph: &G, largest_component: bool) -> PathsReport {
+    let distribution = distance_distribution(graph, largest_component);
+    PathsReport::new(graph, &graph.components(), largest_component, &distribution)
 }
 
-//Define methods for the Graph struct
-impl Graph {
+fn distance_distribution<G: ReviewGraph>(graph: &G, largest_component: bool) -> DistanceDistribution {
+    if largest_component {
+        graph.distance_distribution_largest_component()
+    } else {
+        graph.distance_distribution()
+    }
+}
 
-    //Creates and returns a new graph
-    //Initialization of graph by return an empty adjacency list
-    //creating a new hashmap
-    fn new() -> Graph {
-        Graph {
-            outedges: HashMap::new(),
+//Comparing randomly selected sample sets
+//Evaluates whether enough pairs of each graph are 6 or fewer hops apart
+//and which graph has the shortest average
+fn compare_average_shortpaths(graphs: &[Graph], largest_component: bool) -> CompareReport {
+    let samples: Vec<PathsReport> = graphs.iter().map(|g| summarise_paths(g, largest_component)).collect();
+
+    let mut shortest: Option<(usize, f64)> = None;
+    let mut tied = false;
+    for (i, sample) in samples.iter().enumerate() {
+        let Some(length) = sample.paths.average else { continue };
+        match shortest {
+            None => shortest = Some((i, length)),
+            Some((_, best_length)) => {
+                if length < best_length {
+                    shortest = Some((i, length));
+                    tied = false;
+                } else if length == best_length {
+                    tied = true;
+                }
+            }
         }
     }
+    let shortest = if tied || samples.len() < 2 { None } else { shortest.map(|(i, _)| i) };
+    let summary = summarize(&averages(&samples));
+    CompareReport { samples, shortest, summary, versus: None }
+}
 
-    //Adds an undirected edge between vertices u and v
-    fn add_edges(&mut self, u: String, v: String) {
-        self.outedges.entry(u.clone()).or_insert_with(HashSet::new).insert(v.clone());
-        self.outedges.entry(v).or_insert_with(HashSet::new).insert(u);
-    }
+//Average shortest paths of the samples that have a connected pair
+fn averages(samples: &[PathsReport]) -> Vec<f64> {
+    samples.iter().filter_map(|s| s.paths.average).collect()
+}
 
-    //Creates undriected graph from a list of edges
-    //Iterates over each tuple and adds each edge to the graph
-    fn create_undirected(edges: &[(String, String)]) -> Graph {
-        let mut g = Graph::new();
-        for &(ref u, ref v) in edges {
-            g.add_edges(u.clone(), v.clone());
-        }
-        g
+//Draws the samples of a second configuration and tests their averages against the first
+fn compare_versus(
+    samples: &[PathsReport],
+    versus: Vec<Graph>,
+    sampler: SampleMethod,
+    sample_size: usize,
+    largest_component: bool,
+    permutations: usize,
+    rng: &mut StdRng,
+) -> VersusReport {
+    let versus: Vec<PathsReport> = versus.iter().map(|g| summarise_paths(g, largest_component)).collect();
+    let (a, b) = (averages(samples), averages(&versus));
+    VersusReport {
+        sampler,
+        sample_size,
+        summary: summarize(&b),
+        welch: welch_t_test(&a, &b),
+        permutations,
+        permutation_p_value: permutation_test(&a, &b, permutations, rng),
+        samples: versus,
     }
+}
 
-    //Breadth-first search to calculate shortest path from start mode
-    fn bfs_shortpath(&self, start: &str) -> HashMap<String,usize> {
-        let mut distances = HashMap::new();
-        let mut queue = VecDeque::new();
-        distances.insert(start.to_string(), 0);
-        queue.push_back(start.to_string());
-
-        while let Some(current) = queue.pop_front() {
-            let current_distance = distances[&current];
-            for neighbor in self.outedges.get(&current).unwrap_or(&HashSet::new()) {
-                if !distances.contains_key(neighbor) {
-                    distances.insert(neighbor.to_string(), current_distance + 1);
-                    queue.push_back(neighbor.to_string());
-                }
-            }
+//Tells the user on stderr which lines were dropped in lenient mode
+fn print_read_report(report: &ReadReport) {
+    if report.skipped > 0 {
+        eprintln!("Skipped {} malformed lines out of {}", report.skipped, report.lines);
+        for e in &report.examples {
+            eprintln!("  {}", e);
         }
-        distances
     }
+    if let Some(e) = &report.aborted {
+        eprintln!("Stopped reading early: {}", e);
+    }
+}
 
-    //Calculates the average shortest path for the graph
-    fn average_shortpath(&self) -> f64 {
-        let mut total_length = 0;
-        let mut total_path = 0;
-        for node in self.outedges.keys() {
-            let distances = self.bfs_shortpath(node);
-            for &distance in distances.values() {
-                if distance > 0 {
-                    total_length += distance;
-                    total_path += 1;
-                }
-            }
+//Opens the input dump, exiting with a message if it cannot be opened
+fn open_reviews(cli: &Cli) -> ReviewReader {
+    let mode = if cli.lenient { ParseMode::Lenient } else { ParseMode::Strict };
+    ReviewReader::open(&cli.input, mode).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", cli.input, e);
+        process::exit(1);
+    })
+}
+
+//Passes the graph fields of every review to f, exiting with a message on the first read error
+//Graph passes never need the text of the reviews, so it is skipped instead of copied
+fn edges_or_exit<F: FnMut(ReviewEdge)>(reader: &mut ReviewReader, f: F) {
+    reader.for_each_edge(f).unwrap_or_else(|e| {
+        eprintln!("Could not read review: {}", e);
+        process::exit(1);
+    })
+}
+
+//Collects the IDs to sample from in a first pass over the input
+//Lenient read problems are reported here, later passes see the same lines
+fn read_unique_ids(cli: &Cli) -> Vec<(NodeKind, String)> {
+    let mut reader = open_reviews(cli);
+    let mut ids = UniqueIds::default();
+    edges_or_exit(&mut reader, |review| ids.insert(&review));
+    print_read_report(reader.report());
+    ids.into_sorted()
+}
+
+//Builds the graph of the reviews touching the sampled IDs in another pass over the input
+fn build_sample_graph(cli: &Cli, ids: &SampledIds) -> Graph {
+    let mut reader = open_reviews(cli);
+    let mut builder = GraphBuilder::new();
+    edges_or_exit(&mut reader, |review| {
+        if ids.touches(&review) {
+            builder.add_review(&review);
         }
-        total_length as f64/ total_path as f64
-    }
+    });
+    builder.build()
 }
 
-//Read Jsonfile
-fn read_jsonfile(file_path: &str) -> Vec<Review> {
-    let file = File::open(file_path).expect("Could not open file");
-    let buf_reader = BufReader::new(file);
-    let decoder = GzDecoder::new(buf_reader);
-    let reader = BufReader::new(decoder);
+//Builds the graph of the whole input
+fn read_full_graph(cli: &Cli) -> Graph {
+    let mut reader = open_reviews(cli);
+    let mut builder = GraphBuilder::new();
+    edges_or_exit(&mut reader, |review| builder.add_review(&review));
+    print_read_report(reader.report());
+    builder.build()
+}
 
-    let mut result: Vec<Review> = Vec::new();
+//What samples are drawn from: node sampling only needs the IDs and streams the reviews
+//again for every sample, the other samplers walk the full graph
+//Standard input can only be read once, so node sampling walks the full graph there too
+enum Population {
+    Ids(Vec<(NodeKind, String)>),
+    Graph(Box<Graph>),
+}
 
-    for line in reader.lines() {
-        let line = line.expect("Error reading line from file");
-        let review: Review = serde_json::from_str(&line).expect("Invalid Json");
-        result.push(review);
+fn read_population(cli: &Cli, sampler: SampleMethod) -> Population {
+    match sampler {
+        SampleMethod::Node if cli.input != STDIN => Population::Ids(read_unique_ids(cli)),
+        _ => Population::Graph(Box::new(read_full_graph(cli))),
     }
-    result
 }
 
-//Randomly samples a subset of reviews to reduce the size of the graph
-//Stores randomly selected reviews in sample_ids 
-//Creating a Hashset from the selected sample_ids into sample_id_set
-//Filters reviews to only include reviewer ID and ASIN
-fn sample_reviews(reviews: &[Review], _target_size: usize) -> Vec<(String, String)> {
-    let mut rng = rand::thread_rng();
-    let unique_ids: HashSet<_> = reviews
-        .iter()
-        .flat_map(|r| vec![r.reviewer_id.clone(), r.asin.clone()])
-        .collect();
-    let sample_ids: Vec<_> = unique_ids.into_iter().collect::<Vec<_>>().choose_multiple(&mut rng, _target_size).cloned().collect();
-
-    let sample_id_set: HashSet<_> = sample_ids.into_iter().collect();
-    reviews
-        .iter()
-        .filter(|r| sample_id_set.contains(&r.reviewer_id) || sample_id_set.contains(&r.asin))
-        .map(|r| (r.reviewer_id.clone(), r.asin.clone()))
-        .collect()
+//Draws one sample graph from the population
+fn draw_sample(cli: &Cli, population: &Population, sampler: SampleMethod, size: usize, rng: &mut StdRng) -> Graph {
+    match population {
+        Population::Ids(ids) => build_sample_graph(cli, &sample_ids(ids, size, rng)),
+        Population::Graph(graph) => graph.sample(sampler, size, rng),
+    }
 }
 
-//Comparing two randomly selected sample sets
-//Evaluates whether the shortest path length is 6 or few
-fn compare_average_shortpaths(graph1: &Graph, graph2: &Graph) {
-    let avg_length1 = graph1.average_shortpath();
-    let avg_length2 = graph2.average_shortpath();
-
-    println!("Graph 1: Average Shortest Path Length = {:.2}", avg_length1);
+//Builds the graph of one sample, or of the whole input when no sample size is given
+fn load_graph(cli: &Cli, args: &GraphArgs) -> Graph {
+    match args.sample_size {
+        Some(size) => draw_sample(cli, &read_population(cli, args.sampler), args.sampler, size, &mut make_rng(args.seed)),
+        None => read_full_graph(cli),
+    }
+}
 
-    let six_degrees_1 = avg_length1 <= 6.0;
-    if six_degrees_1 {
-        println!("Six degrees of separation hold true for Graph 1.");
-    } else {
-        println!("Six degrees of separation do not hold true for Graph 1.");
+//The reviews the filter options keep and the vertices they connect, as a view over the graph
+//None when no filter option is given
+//The filter applies after sampling, so the same seed gives the unfiltered graph to compare with
+fn filter_view<'a>(graph: &'a Graph, args: &GraphArgs) -> Option<GraphView<'a>> {
+    let filter = args.filter.edge_filter();
+    if filter.is_empty() {
+        return None;
     }
+    let view = graph.filtered(&filter);
+    eprintln!(
+        "Filter keeps {} of {} reviews between {} of {} reviewers and games",
+        view.edge_count(),
+        graph.edge_count(),
+        view.node_count(),
+        graph.node_count()
+    );
+    Some(view)
+}
 
-    println!("Graph 2: Average Shortest Path Length = {:.2}", avg_length2);
+//Same as load_graph narrowed to the filter, for the analyses that need a graph of their own
+//The kept reviews are copied once, paths, components, distances, degrees and estimates
+//run on the view instead
+fn load_filtered_graph(cli: &Cli, args: &GraphArgs) -> Graph {
+    let graph = load_graph(cli, args);
+    let filtered = filter_view(&graph, args).map(|view| view.to_graph());
+    filtered.unwrap_or(graph)
+}
 
-    let six_degrees_2 = avg_length2 <= 6.0;
-    if six_degrees_2 {
-        println!("Six degrees of separation hold true for Graph 2.");
-    } else {
-        println!("Six degrees of separation do not hold true for Graph 2.");
+//Estimates the average shortest path of a graph from random BFS sources
+fn estimate_paths<G: ReviewGraph>(graph: &G, args: &EstimateArgs) -> EstimateReport {
+    EstimateReport {
+        nodes: graph.node_count(),
+        edges: graph.edge_count(),
+        estimate: graph.estimate_average_shortpath(args.sources, args.bootstrap, &mut make_rng(args.graph.seed)),
     }
+}
 
-    if avg_length1 < avg_length2 {
-        println!("Graph 1 has a shorter average shortest path.");
-    } else if avg_length1 > avg_length2 {
-        println!{"Graph 2 has a shorter average shortest path."};
-    } else {
-        println!("Both graphs have the same average shortest path.");
+//Degree distributions of both sides of a graph
+fn degree_report<G: ReviewGraph>(graph: &G, histogram: bool) -> DegreesReport {
+    DegreesReport {
+        reviewers: graph.degree_stats(NodeKind::Reviewer),
+        products: graph.degree_stats(NodeKind::Product),
+        histogram,
+    }
+}
+
+//Writes the complementary CDF of each side as kind,degree,ccdf rows
+fn write_ccdf(path: &str, sides: &[&DegreeStats]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "kind,degree,ccdf")?;
+    for stats in sides {
+        let kind = match stats.kind {
+            NodeKind::Reviewer => "reviewer",
+            NodeKind::Product => "product",
+        };
+        for (degree, share) in stats.ccdf() {
+            writeln!(out, "{},{},{}", kind, degree, share)?;
+        }
     }
+    out.flush()
+}
+
+//Looks up a reviewer or game by name, exiting with a message if it is not in the graph
+fn find_node(graph: &Graph, kind: NodeKind, name: &str) -> NodeId {
+    graph.node_id(kind, name).unwrap_or_else(|| {
+        eprintln!("No {:?} named {} in the graph", kind, name);
+        process::exit(1);
+    })
 }
 
 //Processing the file
 //Outputs: Execution time of the code
-//Samples a subset of reviews to construct two different graphs
-//Creates two undirect graphs from sample reviews
-//Compare the average shortest path
+//Runs the subcommand chosen on the command line
+//Reviews are streamed from the input, sampling reads it once for the IDs and once per sample
 fn main() {
+    let mut cli = Cli::parse();
     let start = Instant::now();
 
-    let reviews = read_jsonfile("Video_Games_5.json.gz");
+    //A random run without --seed gets a drawn seed, reported with the results so it can be rerun
+    let seed = cli.command.seed_mut().map(|seed| *seed.get_or_insert_with(rand::random));
 
-    let edges1 = sample_reviews(&reviews,1500);
-    let graph1 = Graph::create_undirected(&edges1);
-    
-    let edges2 = sample_reviews(&reviews,1500);
-    let graph2 = Graph::create_undirected(&edges2);
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("The thread pool is only configured once");
+    }
+
+    match &cli.command {
+        Command::Stats => {
+            let mut reader = open_reviews(&cli);
+            let mut builder = GraphBuilder::new();
+            let mut reviews = 0;
+            edges_or_exit(&mut reader, |review| {
+                reviews += 1;
+                builder.add_review(&review);
+            });
+            print_read_report(reader.report());
+            let graph = builder.build();
+            let report = StatsReport {
+                reviews,
+                reviewers: graph.reviewer_count(),
+                products: graph.product_count(),
+                nodes: graph.node_count(),
+                edges: graph.edge_count(),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Paths(args) => {
+            let graph = load_graph(&cli, &args.graph);
+            let report = match filter_view(&graph, &args.graph) {
+                Some(view) => summarise_paths(&view, args.largest_component),
+                None => summarise_paths(&graph, args.largest_component),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Sample(args) => {
+            let mut rng = make_rng(args.seed);
+            let edges = match read_population(&cli, args.sampler) {
+                Population::Ids(ids) => {
+                    let ids = sample_ids(&ids, args.sample_size, &mut rng);
+                    let mut reader = open_reviews(&cli);
+                    let mut edges = Vec::new();
+                    edges_or_exit(&mut reader, |r| {
+                        if ids.touches(&r) {
+                            edges.push(SampleEdge { reviewer_id: r.reviewer_id.into_owned(), asin: r.asin.into_owned() });
+                        }
+                    });
+                    edges
+                }
+                Population::Graph(graph) => {
+                    let sample = graph.sample(args.sampler, args.sample_size, &mut rng);
+                    sample
+                        .reviewers()
+                        .flat_map(|u| sample.neighbors(u).iter().map(move |&v| (u, v)))
+                        .map(|(u, v)| SampleEdge {
+                            reviewer_id: sample.name(u).to_string(),
+                            asin: sample.name(v).to_string(),
+                        })
+                        .collect()
+                }
+            };
+            emit(cli.format, seed, &SampleReport { edges });
+        }
+        Command::Compare(args) => {
+            let mut rng = make_rng(args.sample.seed);
+            let population = read_population(&cli, args.sample.sampler);
+            let graphs: Vec<Graph> = (0..args.samples)
+                .map(|_| draw_sample(&cli, &population, args.sample.sampler, args.sample.sample_size, &mut rng))
+                .collect();
+            let mut report = compare_average_shortpaths(&graphs, args.largest_component);
+            if args.versus_sampler.is_some() || args.versus_size.is_some() {
+                let sampler = args.versus_sampler.unwrap_or(args.sample.sampler);
+                let size = args.versus_size.unwrap_or(args.sample.sample_size);
+                //A full graph already read serves the second configuration unless it streams node samples
+                let population = match population {
+                    Population::Graph(graph) if sampler != SampleMethod::Node || cli.input == STDIN => {
+                        Population::Graph(graph)
+                    }
+                    _ => read_population(&cli, sampler),
+                };
+                let versus: Vec<Graph> = (0..args.samples)
+                    .map(|_| draw_sample(&cli, &population, sampler, size, &mut rng))
+                    .collect();
+                report.versus = Some(compare_versus(
+                    &report.samples,
+                    versus,
+                    sampler,
+                    size,
+                    args.largest_component,
+                    args.permutations,
+                    &mut rng,
+                ));
+            }
+            emit(cli.format, seed, &report);
+        }
+        Command::Project(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let projection = graph.projection(args.side, args.normalize, args.min_overlap);
+            let report = ProjectionReport {
+                side: args.side,
+                normalization: args.normalize,
+                min_overlap: args.min_overlap,
+                edges: projection
+                    .edges
+                    .iter()
+                    .map(|e| ProjectionEdge {
+                        u: graph.name(e.u).to_string(),
+                        v: graph.name(e.v).to_string(),
+                        shared: e.shared,
+                        weight: e.weight,
+                    })
+                    .collect(),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Estimate(args) => {
+            let graph = load_graph(&cli, &args.graph);
+            let report = match filter_view(&graph, &args.graph) {
+                Some(view) => estimate_paths(&view, args),
+                None => estimate_paths(&graph, args),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Components(args) => {
+            let graph = load_graph(&cli, &args.graph);
+            let report = match filter_view(&graph, &args.graph) {
+                Some(view) => ComponentsReport::new(&view, &view.components(), args.assignments),
+                None => ComponentsReport::new(&graph, &graph.components(), args.assignments),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Distances(args) => {
+            let graph = load_graph(&cli, &args.graph);
+            let (lc, eccentricity) = (args.largest_component, args.eccentricity);
+            let report = match filter_view(&graph, &args.graph) {
+                Some(view) => DistancesReport::new(&view, &distance_distribution(&view, lc), lc, eccentricity),
+                None => DistancesReport::new(&graph, &distance_distribution(&graph, lc), lc, eccentricity),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Path(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let from = find_node(&graph, args.from_kind, &args.from);
+            let to = find_node(&graph, args.to_kind, &args.to);
+            let (paths, cost) = match args.cost {
+                Some(edge_cost) => match graph.weighted_shortest_path(from, to, |a| edge_cost.cost(a)) {
+                    Some((cost, path)) => (vec![path], Some(cost)),
+                    None => (Vec::new(), None),
+                },
+                None if args.all => (graph.all_shortest_paths(from, to, args.limit), None),
+                None => (graph.shortest_path(from, to).into_iter().collect(), None),
+            };
+            emit(cli.format, seed, &PathReport::new(&graph, from, to, paths, cost));
+        }
+        Command::Neighborhood(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let mut seeds: Vec<NodeId> = args.seed_node.iter().map(|name| find_node(&graph, args.seed_kind, name)).collect();
+            for asin in &args.reviewers_of {
+                let game = find_node(&graph, NodeKind::Product, asin);
+                seeds.extend_from_slice(graph.neighbors(game));
+            }
+            let report = NeighborhoodReport {
+                hops: args.hops,
+                nodes: graph
+                    .neighborhood(&seeds, args.hops)
+                    .into_iter()
+                    .map(|(u, distance)| NeighborhoodNode { kind: graph.kind(u), name: graph.name(u).to_string(), distance })
+                    .collect(),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Within(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let n = graph.node_count() as u64;
+            let pairs = n * n.saturating_sub(1);
+            let pairs_within = graph.pairs_within(args.hops);
+            let report = WithinReport {
+                hops: args.hops,
+                pairs,
+                pairs_within,
+                share: (pairs > 0).then(|| pairs_within as f64 / pairs as f64),
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Communities(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let communities = graph.communities(args.method);
+            emit(cli.format, seed, &CommunitiesReport::new(&graph, &communities, args.limit, args.top, args.members));
+        }
+        Command::Centrality(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let scores = graph.centrality(args.measure, args.sources, args.damping, &mut make_rng(args.graph.seed));
+            emit(cli.format, seed, &CentralityReport::new(&graph, &scores, args.sources, args.top));
+        }
+        Command::Degrees(args) => {
+            let graph = load_graph(&cli, &args.graph);
+            let report = match filter_view(&graph, &args.graph) {
+                Some(view) => degree_report(&view, args.histogram),
+                None => degree_report(&graph, args.histogram),
+            };
+            if let Some(path) = &args.ccdf {
+                if let Err(e) = write_ccdf(path, &[&report.reviewers, &report.products]) {
+                    eprintln!("Could not write {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+            emit(cli.format, seed, &report);
+        }
+        Command::NullModel(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let test = graph.null_model_test(args.models, args.swaps, args.largest_component, &mut make_rng(args.graph.seed));
+            let report = NullModelReport {
+                nodes: graph.node_count(),
+                edges: graph.edge_count(),
+                largest_component: args.largest_component,
+                test,
+            };
+            emit(cli.format, seed, &report);
+        }
+        Command::Snapshots(args) => {
+            let graph = load_filtered_graph(&cli, &args.graph);
+            let sampling = PathSampling { sources: args.sources, exact_limit: args.exact_limit };
+            let mut rng = make_rng(args.graph.seed);
+            let report = SnapshotsReport {
+                period: args.period,
+                mode: args.mode,
+                window: args.window,
+                largest_component: args.largest_component,
+                sources: args.sources,
+                exact_limit: args.exact_limit,
+                snapshots: graph.snapshots(args.period, args.mode, args.window as usize, args.largest_component, sampling, &mut rng),
+            };
+            emit(cli.format, seed, &report);
+        }
+    }
 
-    compare_average_shortpaths(&graph1, &graph2);
-    
     let duration = start.elapsed();
-    println!("Time elapsed is: {:?}", duration);
+    eprintln!("Time elapsed is: {:?}", duration);
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[test]
-    fn test_bfs_shortpath() {
-        let edges = vec! [
-            ("A".to_string(), "B".to_string()),
-            ("B".to_string(), "C".to_string()),
-            ("C".to_string(), "D".to_string()),
-        ];
-
-        let graph = Graph::create_undirected(&edges);
-        let distances = graph.bfs_shortpath("A");
-        assert_eq!(dsitances.get("B"), Some(&1));
-        assert_eq!(dsitances.get("C"), Some(&2));
-        assert_eq!(dsitances.get("D"), Some(&3));
-    }
-
-    #[test]
-    fn test_average_shortpath() {
-        let edges = vec![
-            ("A".to_string(), "B".to_string()),
-            ("B".to_string(), "C".to_string()),
-            ("C".to_string(), "D".to_string()),
-        ];
-        let graph = Graph::create_undirected(&edges);
-        let avg_shortpath = graph.average_shortpath();
-        assert_eq!(avg_shortpath, 1.5);
-    }
-
-    #[test]
-    fn test_sample_reviews() {
-        let reveiws = vec![
-            Review {
-                reviewer_id: "A1".to_string(),
-                asin: "B1".to_string(),
-            },
-
-            Review {
-                reviewer_id: "A2".to_string(),
-                asin: "B2".to_string(),
-            },
-            
-            Review {
-                reviewer_id: "A3".to_string(),
-                asin: "B3".to_string(),
-            },
-        ];
-        let samples = sample_reviews(&reviews,2);
-        assert_eq!(samples.len(),2)
-    }
-
     #[test]
     fn test_compare_average_shortpaths() {
         let edges1 = vec![
             ("A".to_string(), "B".to_string()),
-            ("B".to_string(), "C".to_string()),
+            ("C".to_string(), "B".to_string()),
         ];
 
         let edges2 = vec![
             ("A".to_string(), "B".to_string()),
-            ("B".to_string(), "D".to_string()),
+            ("D".to_string(), "B".to_string()),
             ("D".to_string(), "E".to_string()),
         ];
 
         let graph1 = Graph::create_undirected(&edges1);
         let graph2 = Graph::create_undirected(&edges2);
-        let avg_length1 = graph1.average_shortpath();
-        let avg_length2 = graph2.average_shortpath();
+        let avg_length1 = graph1.average_shortpath().average.unwrap();
+        let avg_length2 = graph2.average_shortpath().average.unwrap();
         assert!(avg_length1 < avg_length2);
+
+        let report = compare_average_shortpaths(&[graph1, graph2], false);
+        assert_eq!(report.shortest, Some(0));
+        assert!(report.samples.iter().all(|s| s.six_degrees));
+        assert_eq!(report.summary.n, 2);
+        assert_eq!(report.summary.mean, (avg_length1 + avg_length2) / 2.0);
+
+        let empty = Graph::create_undirected(&[]);
+        let report = compare_average_shortpaths(&[empty, Graph::create_undirected(&edges2)], false);
+        assert_eq!(report.shortest, Some(1));
+        assert!(!report.samples[0].six_degrees);
+
+        //Ten separate reviews have an average path of 1, but almost no pair is connected
+        let pairs: Vec<(String, String)> = (0..10).map(|i| (format!("A{}", i), format!("B{}", i))).collect();
+        let report = compare_average_shortpaths(&[Graph::create_undirected(&pairs)], false);
+        assert_eq!(report.samples[0].paths.average, Some(1.0));
+        assert_eq!(report.samples[0].within_six_hops, Some(1.0));
+        assert_eq!(report.samples[0].all_pairs_within_six_hops, Some(20.0 / 380.0));
+        assert!(!report.samples[0].six_degrees);
     }
 }
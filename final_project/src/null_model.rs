@@ -0,0 +1,167 @@
+//Degree-preserving null models of the review graph
+//Whether an average path is "small" only means something next to random graphs with the same
+//degrees, so the observed graph is scored against an ensemble of rewired copies
+use crate::graph::{Graph, GraphBuilder, NodeId, NodeKind, ReviewGraph};
+use crate::stats::{mean, variance};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::collections::HashSet;
+
+//One measure of the observed graph against the null ensemble
+//z_score is NaN when the null models never differ or fewer than two had a value
+#[derive(Debug, Clone, Serialize)]
+pub struct NullComparison {
+    pub observed: f64,
+    pub null_mean: f64,
+    pub null_sd: f64,
+    pub z_score: f64,
+}
+
+impl NullComparison {
+    pub fn new(observed: f64, null: &[f64]) -> NullComparison {
+        let null_mean = mean(null);
+        let null_sd = variance(null).sqrt();
+        NullComparison {
+            observed,
+            null_mean,
+            null_sd,
+            z_score: if null_sd > 0.0 { (observed - null_mean) / null_sd } else { f64::NAN },
+        }
+    }
+}
+
+//Average shortest path, clustering and largest component of a graph against its null ensemble
+//The average path only includes null models with a connected pair
+#[derive(Debug, Clone, Serialize)]
+pub struct NullModelTest {
+    pub models: usize,
+    pub swaps_per_edge: usize,
+    pub average_shortpath: NullComparison,
+    pub clustering: NullComparison,
+    pub largest_component: NullComparison,
+}
+
+//The three measures compared against the null models
+fn measures(graph: &Graph, largest_component: bool) -> (Option<f64>, f64, f64) {
+    let paths = if largest_component {
+        graph.average_shortpath_largest_component()
+    } else {
+        graph.average_shortpath()
+    };
+    let largest = graph.components().largest_size() as f64;
+    (paths.average, graph.bipartite_clustering(), largest)
+}
+
+impl Graph {
+    //Random graph with the same reviewers, games and degrees, from repeated double edge swaps
+    //Two reviews (r1, g1) and (r2, g2) become (r1, g2) and (r2, g1) unless that would repeat a review,
+    //swaps_per_edge swaps are attempted for every edge. Vertices keep their NodeIds,
+    //the rewired edges no longer stand for real reviews and carry default attributes
+    pub fn rewired<R: Rng>(&self, swaps_per_edge: usize, rng: &mut R) -> Graph {
+        let mut edges: Vec<(NodeId, NodeId)> = self
+            .nodes_of(NodeKind::Reviewer)
+            .flat_map(|r| self.neighbors(r).iter().map(move |&g| (r, g)))
+            .collect();
+        let mut present: HashSet<(NodeId, NodeId)> = edges.iter().copied().collect();
+        if edges.len() >= 2 {
+            for _ in 0..swaps_per_edge * edges.len() {
+                let i = rng.gen_range(0..edges.len());
+                let j = rng.gen_range(0..edges.len());
+                let ((r1, g1), (r2, g2)) = (edges[i], edges[j]);
+                if r1 == r2 || g1 == g2 || present.contains(&(r1, g2)) || present.contains(&(r2, g1)) {
+                    continue;
+                }
+                present.remove(&(r1, g1));
+                present.remove(&(r2, g2));
+                present.insert((r1, g2));
+                present.insert((r2, g1));
+                edges[i] = (r1, g2);
+                edges[j] = (r2, g1);
+            }
+        }
+
+        let mut builder = GraphBuilder::new();
+        for u in self.nodes() {
+            builder.add_node(self.kind(u), self.name(u));
+        }
+        for (r, g) in edges {
+            builder.add_edge_ids(r, g);
+        }
+        builder.build()
+    }
+
+    //Scores the average shortest path, clustering and largest component against `models` rewired graphs
+    //Each null model has its own generator seeded from rng, so the ensemble is reproducible
+    pub fn null_model_test<R: Rng>(
+        &self,
+        models: usize,
+        swaps_per_edge: usize,
+        largest_component: bool,
+        rng: &mut R,
+    ) -> NullModelTest {
+        let (paths, clustering, largest) = measures(self, largest_component);
+        let mut null_paths = Vec::new();
+        let mut null_clustering = Vec::new();
+        let mut null_largest = Vec::new();
+        for _ in 0..models {
+            let mut model_rng = StdRng::seed_from_u64(rng.gen());
+            let null = self.rewired(swaps_per_edge, &mut model_rng);
+            let (p, c, l) = measures(&null, largest_component);
+            null_paths.extend(p);
+            null_clustering.push(c);
+            null_largest.push(l);
+        }
+        NullModelTest {
+            models,
+            swaps_per_edge,
+            average_shortpath: NullComparison::new(paths.unwrap_or(f64::NAN), &null_paths),
+            clustering: NullComparison::new(clustering, &null_clustering),
+            largest_component: NullComparison::new(largest, &null_largest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::two_game_graph;
+
+    #[test]
+    fn test_rewiring_preserves_degrees() {
+        let graph = two_game_graph(60, 9, 13);
+        let mut rng = StdRng::seed_from_u64(4);
+        let rewired = graph.rewired(10, &mut rng);
+        assert_eq!(rewired.node_count(), graph.node_count());
+        assert_eq!(rewired.edge_count(), graph.edge_count());
+        assert!(rewired.is_bipartite());
+        assert!(graph.nodes().all(|u| rewired.degree(u) == graph.degree(u) && rewired.name(u) == graph.name(u)));
+        assert!(graph.nodes().any(|u| rewired.neighbors(u) != graph.neighbors(u)));
+    }
+
+    #[test]
+    fn test_null_model_test() {
+        let graph = two_game_graph(60, 9, 13);
+        let mut rng = StdRng::seed_from_u64(5);
+        let test = graph.null_model_test(8, 5, false, &mut rng);
+        assert_eq!(test.models, 8);
+        assert_eq!(test.average_shortpath.observed, graph.average_shortpath().average.unwrap());
+        assert_eq!(test.largest_component.observed, graph.node_count() as f64);
+        assert!(test.clustering.null_sd > 0.0);
+        assert!(test.clustering.z_score.is_finite());
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let again = graph.null_model_test(8, 5, false, &mut rng);
+        assert_eq!(again.clustering.null_mean, test.clustering.null_mean);
+    }
+
+    #[test]
+    fn test_null_comparison() {
+        let comparison = NullComparison::new(5.0, &[1.0, 2.0, 3.0]);
+        assert_eq!(comparison.null_mean, 2.0);
+        assert_eq!(comparison.null_sd, 1.0);
+        assert_eq!(comparison.z_score, 3.0);
+        assert!(NullComparison::new(5.0, &[2.0, 2.0]).z_score.is_nan());
+        assert!(NullComparison::new(5.0, &[2.0]).z_score.is_nan());
+    }
+}
@@ -0,0 +1,206 @@
+//Point-to-point shortest paths with the chain of reviewers and games that connects them
+use crate::graph::{Graph, NodeId};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+//One side of a bidirectional search: parent and depth of every vertex seen so far
+struct Side {
+    seen: HashMap<NodeId, (NodeId, u32)>,
+    frontier: Vec<NodeId>,
+}
+
+impl Side {
+    fn new(start: NodeId) -> Side {
+        Side {
+            seen: HashMap::from([(start, (start, 0))]),
+            frontier: vec![start],
+        }
+    }
+
+    //Follows the parents from u back to the start of this side
+    fn chain(&self, mut u: NodeId) -> Vec<NodeId> {
+        let mut chain = vec![u];
+        while let Some(&(parent, depth)) = self.seen.get(&u) {
+            if depth == 0 {
+                break;
+            }
+            chain.push(parent);
+            u = parent;
+        }
+        chain
+    }
+}
+
+impl Graph {
+    //Expands one whole BFS level of `side`, returning the best meeting vertex with `other`
+    fn expand_level(&self, side: &mut Side, other: &Side) -> Option<(u32, NodeId)> {
+        let mut best: Option<(u32, NodeId)> = None;
+        let mut next = Vec::new();
+        for &current in &side.frontier {
+            let depth = side.seen[&current].1 + 1;
+            for &neighbor in self.neighbors(current) {
+                if let Entry::Vacant(entry) = side.seen.entry(neighbor) {
+                    entry.insert((current, depth));
+                    next.push(neighbor);
+                    if let Some(&(_, other_depth)) = other.seen.get(&neighbor) {
+                        let candidate = (depth + other_depth, neighbor);
+                        if best.is_none_or(|b| candidate < b) {
+                            best = Some(candidate);
+                        }
+                    }
+                }
+            }
+        }
+        side.frontier = next;
+        best
+    }
+
+    //Shortest chain of vertices from u to v, both included, None if they are not connected
+    //Bidirectional BFS grows the smaller frontier one whole level at a time and stops
+    //at the first level where the two searches meet
+    pub fn shortest_path(&self, u: NodeId, v: NodeId) -> Option<Vec<NodeId>> {
+        if u == v {
+            return Some(vec![u]);
+        }
+        let mut forward = Side::new(u);
+        let mut backward = Side::new(v);
+        while !forward.frontier.is_empty() && !backward.frontier.is_empty() {
+            let meeting = if forward.frontier.len() <= backward.frontier.len() {
+                self.expand_level(&mut forward, &backward)
+            } else {
+                self.expand_level(&mut backward, &forward)
+            };
+            if let Some((_, middle)) = meeting {
+                let mut path = forward.chain(middle);
+                path.reverse();
+                path.extend(backward.chain(middle).into_iter().skip(1));
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    //BFS distances from start to every vertex at most max_depth hops away
+    fn distances_within(&self, start: NodeId, max_depth: u32) -> HashMap<NodeId, u32> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut frontier = vec![start];
+        for depth in 1..=max_depth {
+            let mut next = Vec::new();
+            for &current in &frontier {
+                for &neighbor in self.neighbors(current) {
+                    if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                        entry.insert(depth);
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        distances
+    }
+
+    //Every shortest chain from u to v, at most limit of them, in lexicographic NodeId order
+    //A vertex w lies on a shortest path exactly when d(u, w) + d(w, v) = d(u, v),
+    //so the paths are enumerated by walking forward only through such vertices
+    pub fn all_shortest_paths(&self, u: NodeId, v: NodeId, limit: usize) -> Vec<Vec<NodeId>> {
+        let length = match self.shortest_path(u, v) {
+            Some(path) => (path.len() - 1) as u32,
+            None => return Vec::new(),
+        };
+        let from_u = self.distances_within(u, length);
+        let from_v = self.distances_within(v, length);
+        let on_path = |w: NodeId, depth: u32| {
+            from_u.get(&w) == Some(&depth) && from_v.get(&w) == Some(&(length - depth))
+        };
+
+        let mut paths = Vec::new();
+        let mut path = vec![u];
+        let mut stack: Vec<usize> = vec![0];
+        while let Some(&next) = stack.last() {
+            if paths.len() >= limit {
+                break;
+            }
+            let current = *path.last().unwrap();
+            if current == v {
+                paths.push(path.clone());
+                stack.pop();
+                path.pop();
+                continue;
+            }
+            let depth = path.len() as u32;
+            let neighbors = self.neighbors(current);
+            match (next..neighbors.len()).find(|&i| on_path(neighbors[i], depth)) {
+                Some(i) => {
+                    *stack.last_mut().unwrap() = i + 1;
+                    path.push(neighbors[i]);
+                    stack.push(0);
+                }
+                None => {
+                    stack.pop();
+                    path.pop();
+                }
+            }
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{two_game_graph, NodeKind};
+
+    //Reviewers A and D both reach reviewer C through two games each, E is on its own
+    fn diamond_graph() -> Graph {
+        let edges = vec![
+            ("A".to_string(), "G1".to_string()),
+            ("A".to_string(), "G2".to_string()),
+            ("B".to_string(), "G1".to_string()),
+            ("B".to_string(), "G2".to_string()),
+            ("B".to_string(), "G3".to_string()),
+            ("C".to_string(), "G3".to_string()),
+            ("E".to_string(), "G4".to_string()),
+        ];
+        Graph::create_undirected(&edges)
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = diamond_graph();
+        let r = |name| graph.node_id(NodeKind::Reviewer, name).unwrap();
+        let path = graph.shortest_path(r("A"), r("C")).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[0], r("A"));
+        assert_eq!(path[2], r("B"));
+        assert_eq!(graph.name(path[3]), "G3");
+        assert_eq!(path[4], r("C"));
+        assert!(path.windows(2).all(|w| graph.neighbors(w[0]).contains(&w[1])));
+
+        assert_eq!(graph.shortest_path(r("A"), r("A")), Some(vec![r("A")]));
+        assert_eq!(graph.shortest_path(r("A"), r("E")), None);
+    }
+
+    #[test]
+    fn test_shortest_path_length_matches_bfs() {
+        let graph = two_game_graph(50, 7, 11);
+        for u in graph.nodes().step_by(5) {
+            let distances = graph.bfs_shortpath(u);
+            for v in graph.nodes() {
+                let path = graph.shortest_path(u, v).unwrap();
+                assert_eq!(path.len() as u32 - 1, distances[v as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let graph = diamond_graph();
+        let r = |name| graph.node_id(NodeKind::Reviewer, name).unwrap();
+        let paths = graph.all_shortest_paths(r("A"), r("C"), 10);
+        assert_eq!(paths.len(), 2);
+        let via: Vec<&str> = paths.iter().map(|p| graph.name(p[1])).collect();
+        assert_eq!(via, vec!["G1", "G2"]);
+        assert_eq!(graph.all_shortest_paths(r("A"), r("C"), 1).len(), 1);
+        assert!(graph.all_shortest_paths(r("A"), r("E"), 10).is_empty());
+    }
+}
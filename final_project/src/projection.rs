@@ -0,0 +1,137 @@
+//One-mode projections of the reviewer/game graph
+//Two games are linked when they share reviewers, two reviewers when they share games
+use crate::graph::{Graph, NodeId, NodeKind};
+use clap::ValueEnum;
+use serde::Serialize;
+
+//How the shared-neighbour count of a projected edge is turned into its weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    //The raw number of shared neighbours
+    Count,
+    //Shared neighbours over the size of the union of both neighbourhoods
+    Jaccard,
+    //Shared neighbours over the geometric mean of both degrees
+    Cosine,
+}
+
+//One edge of a projection, u < v, both on the projected side of the original graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectedEdge {
+    pub u: NodeId,
+    pub v: NodeId,
+    pub shared: usize,
+    pub weight: f64,
+}
+
+//Weighted graph over one side of a bipartite Graph
+//Vertex ids are those of the original graph, so names and degrees are looked up there
+#[derive(Debug, Clone)]
+pub struct Projection {
+    pub kind: NodeKind,
+    pub normalization: Normalization,
+    pub edges: Vec<ProjectedEdge>,
+}
+
+impl Projection {
+    //Weight of the edge between u and v, if they share enough neighbours
+    pub fn weight(&self, u: NodeId, v: NodeId) -> Option<f64> {
+        let key = (u.min(v), u.max(v));
+        self.edges
+            .binary_search_by(|e| (e.u, e.v).cmp(&key))
+            .ok()
+            .map(|i| self.edges[i].weight)
+    }
+}
+
+impl Graph {
+    //Projects the graph onto the vertices of one kind
+    //Pairs sharing fewer than min_overlap neighbours are left out
+    //Shared neighbours of u are counted in a dense array reset after each vertex,
+    //and only pairs with v > u are kept so each edge is produced once in sorted order
+    pub fn projection(&self, kind: NodeKind, normalization: Normalization, min_overlap: usize) -> Projection {
+        let min_overlap = min_overlap.max(1);
+        let mut shared = vec![0usize; self.node_count()];
+        let mut touched: Vec<NodeId> = Vec::new();
+        let mut edges = Vec::new();
+
+        for u in self.nodes_of(kind) {
+            for &via in self.neighbors(u) {
+                for &v in self.neighbors(via) {
+                    if v > u && self.kind(v) == kind {
+                        if shared[v as usize] == 0 {
+                            touched.push(v);
+                        }
+                        shared[v as usize] += 1;
+                    }
+                }
+            }
+            touched.sort_unstable();
+            for &v in &touched {
+                let count = shared[v as usize];
+                shared[v as usize] = 0;
+                if count < min_overlap {
+                    continue;
+                }
+                let (du, dv) = (self.degree(u) as f64, self.degree(v) as f64);
+                let weight = match normalization {
+                    Normalization::Count => count as f64,
+                    Normalization::Jaccard => count as f64 / (du + dv - count as f64),
+                    Normalization::Cosine => count as f64 / (du * dv).sqrt(),
+                };
+                edges.push(ProjectedEdge { u, v, shared: count, weight });
+            }
+            touched.clear();
+        }
+
+        Projection { kind, normalization, edges }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //Games G1 and G2 share reviewers R1 and R2, G2 and G3 share R3
+    fn review_graph() -> Graph {
+        let edges = vec![
+            ("R1".to_string(), "G1".to_string()),
+            ("R1".to_string(), "G2".to_string()),
+            ("R2".to_string(), "G1".to_string()),
+            ("R2".to_string(), "G2".to_string()),
+            ("R3".to_string(), "G2".to_string()),
+            ("R3".to_string(), "G3".to_string()),
+        ];
+        Graph::create_undirected(&edges)
+    }
+
+    #[test]
+    fn test_product_projection() {
+        let graph = review_graph();
+        let g = |name| graph.node_id(NodeKind::Product, name).unwrap();
+
+        let counts = graph.projection(NodeKind::Product, Normalization::Count, 1);
+        assert_eq!(counts.edges.len(), 2);
+        assert_eq!(counts.weight(g("G1"), g("G2")), Some(2.0));
+        assert_eq!(counts.weight(g("G3"), g("G2")), Some(1.0));
+        assert_eq!(counts.weight(g("G1"), g("G3")), None);
+
+        let jaccard = graph.projection(NodeKind::Product, Normalization::Jaccard, 2);
+        assert_eq!(jaccard.edges.len(), 1);
+        assert_eq!(jaccard.weight(g("G1"), g("G2")), Some(2.0 / 3.0));
+
+        let cosine = graph.projection(NodeKind::Product, Normalization::Cosine, 1);
+        assert_eq!(cosine.weight(g("G2"), g("G3")), Some(1.0 / 3.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn test_reviewer_projection() {
+        let graph = review_graph();
+        let r = |name| graph.node_id(NodeKind::Reviewer, name).unwrap();
+        let projection = graph.projection(NodeKind::Reviewer, Normalization::Count, 1);
+        assert_eq!(projection.weight(r("R1"), r("R2")), Some(2.0));
+        assert_eq!(projection.weight(r("R2"), r("R3")), Some(1.0));
+        assert_eq!(projection.weight(r("R1"), r("R3")), Some(1.0));
+    }
+}
@@ -0,0 +1,554 @@
+//Reading reviews out of a JSON-lines dump, plain or compressed with gzip, zstd or bzip2
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+//Creating a struct named "Review" to group data
+//Deserialize tells Serde how to interpret the data
+//Identifiying the traits/fields used for this code
+//Only reviewerID and asin are required, the other fields of the Amazon review schema
+//are missing from some records and dumps, so they are optional
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Review {
+    #[serde(rename = "reviewerID")]
+    pub reviewer_id: String,
+    pub asin: String,
+    #[serde(rename = "reviewerName", default)]
+    pub reviewer_name: Option<String>,
+    //Star rating from 1 to 5
+    #[serde(default)]
+    pub overall: Option<f32>,
+    //Seconds since the Unix epoch
+    #[serde(rename = "unixReviewTime", default)]
+    pub unix_review_time: Option<i64>,
+    //The same date as written in the dump, e.g. "09 13, 2009"
+    #[serde(rename = "reviewTime", default)]
+    pub review_time: Option<String>,
+    #[serde(rename = "reviewText", default)]
+    pub review_text: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    //Helpful votes, stored in the dump as a string with thousands separators such as "1,234"
+    #[serde(default, deserialize_with = "deserialize_vote")]
+    pub vote: Option<u32>,
+    #[serde(default)]
+    pub verified: Option<bool>,
+    //Product variant such as {"Format": "Hardcover"}, without the trailing colons and padding of the dump
+    #[serde(default, deserialize_with = "deserialize_style")]
+    pub style: Option<BTreeMap<String, String>>,
+    //URLs of the images attached to the review
+    #[serde(default)]
+    pub image: Option<Vec<String>>,
+}
+
+impl Review {
+    //The fields the graph is built from, borrowed from this review
+    pub fn edge(&self) -> ReviewEdge<'_> {
+        ReviewEdge {
+            reviewer_id: Cow::Borrowed(&self.reviewer_id),
+            asin: Cow::Borrowed(&self.asin),
+            overall: self.overall,
+            unix_review_time: self.unix_review_time,
+            vote: self.vote,
+            verified: self.verified,
+        }
+    }
+}
+
+//The part of a review that becomes an edge of the graph, read by ReviewReader::for_each_edge
+//The IDs borrow from the line being parsed, and reviewText, summary, image and the other fields
+//are skipped without being copied, so building a graph allocates nothing for most records.
+//An ID written with JSON escapes cannot be borrowed and is unescaped into an owned copy
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReviewEdge<'a> {
+    #[serde(rename = "reviewerID", borrow)]
+    pub reviewer_id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub asin: Cow<'a, str>,
+    #[serde(default)]
+    pub overall: Option<f32>,
+    #[serde(rename = "unixReviewTime", default)]
+    pub unix_review_time: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_vote")]
+    pub vote: Option<u32>,
+    #[serde(default)]
+    pub verified: Option<bool>,
+}
+
+//A vote count as it appears in a dump: usually a string like "1,234", a plain number in some copies
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawVote {
+    Count(u32),
+    Text(String),
+}
+
+//Parses the vote count, dropping thousands separators
+fn deserialize_vote<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    match Option::<RawVote>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(RawVote::Count(count)) => Ok(Some(count)),
+        Some(RawVote::Text(text)) => text
+            .trim()
+            .replace(',', "")
+            .parse()
+            .map(Some)
+            .map_err(|_| de::Error::custom(format!("invalid vote count {:?}", text))),
+    }
+}
+
+//Trims the style keys and values, "Format:" -> "Format" and " Hardcover" -> "Hardcover"
+fn deserialize_style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<BTreeMap<String, String>>, D::Error> {
+    let style = Option::<BTreeMap<String, String>>::deserialize(deserializer)?;
+    Ok(style.map(|style| {
+        style
+            .into_iter()
+            .map(|(key, value)| (key.trim().trim_end_matches(':').to_string(), value.trim().to_string()))
+            .collect()
+    }))
+}
+
+//Errors that can stop or interrupt reading a review dump
+//Line numbers start at 1
+#[derive(Debug)]
+pub enum ReadError {
+    //The file could not be opened or read
+    Io(io::Error),
+    //The compressed stream is corrupt or truncated
+    Decompress { line: usize, source: io::Error },
+    //A line is not a valid review record
+    Json { line: usize, source: serde_json::Error },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "I/O error: {}", e),
+            ReadError::Decompress { line, source } => {
+                write!(f, "decompression error at line {}: {}", line, source)
+            }
+            ReadError::Json { line, source } => write!(f, "invalid JSON at line {}: {}", line, source),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Decompress { source, .. } => Some(source),
+            ReadError::Json { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> ReadError {
+        ReadError::Io(e)
+    }
+}
+
+//Strict stops at the first malformed line
+//Lenient skips malformed lines and records them in the ReadReport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+//Number of skipped lines kept as examples in a ReadReport
+pub const MAX_EXAMPLES: usize = 10;
+
+//Summary of what was dropped while reading in lenient mode
+//examples holds the first MAX_EXAMPLES skipped lines
+//aborted holds the stream error that ended reading early, if any
+#[derive(Debug, Default)]
+pub struct ReadReport {
+    pub lines: usize,
+    pub skipped: usize,
+    pub examples: Vec<ReadError>,
+    pub aborted: Option<ReadError>,
+}
+
+impl ReadReport {
+    fn skip(&mut self, error: ReadError) {
+        self.skipped += 1;
+        if self.examples.len() < MAX_EXAMPLES {
+            self.examples.push(error);
+        }
+    }
+}
+
+//Errors from the decoders are reported as decompression errors,
+//anything else coming out of the reader is an I/O error
+fn classify_read_error(line: usize, e: io::Error) -> ReadError {
+    match e.kind() {
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+            ReadError::Decompress { line, source: e }
+        }
+        _ => ReadError::Io(e),
+    }
+}
+
+//Input path that reads the dump from standard input
+pub const STDIN: &str = "-";
+
+//How a dump is compressed, told apart by the magic bytes it starts with
+//Concatenated gzip and bzip2 members, as written by parallel compressors, are read as one stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    Plain,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Codec {
+    //Longest magic number that is checked
+    const MAGIC_LEN: usize = 4;
+
+    pub fn detect(magic: &[u8]) -> Codec {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Codec::Gzip
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Codec::Zstd
+        } else if magic.starts_with(b"BZh") {
+            Codec::Bzip2
+        } else {
+            Codec::Plain
+        }
+    }
+}
+
+//Wraps a raw input in the decoder for its compression
+//The magic bytes are read off the input first and put back in front of the rest
+fn decode<R: Read + 'static>(mut input: R) -> io::Result<Box<dyn BufRead>> {
+    let mut magic = Vec::with_capacity(Codec::MAGIC_LEN);
+    input.by_ref().take(Codec::MAGIC_LEN as u64).read_to_end(&mut magic)?;
+    let codec = Codec::detect(&magic);
+    let raw = BufReader::new(io::Cursor::new(magic).chain(input));
+    Ok(match codec {
+        Codec::Plain => Box::new(raw),
+        Codec::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(raw))),
+        Codec::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(raw)?)),
+        Codec::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(raw))),
+    })
+}
+
+//Streams reviews out of a dump one line at a time
+//Blank lines are ignored, every other line must hold one review
+//In strict mode the first malformed line is yielded as an error and ends the stream
+//In lenient mode malformed lines are skipped and recorded in the report instead
+pub struct ReviewReader {
+    reader: Box<dyn BufRead>,
+    mode: ParseMode,
+    report: ReadReport,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl ReviewReader {
+    //Opens the dump at file_path, or standard input for STDIN, detecting its compression
+    pub fn open(file_path: &str, mode: ParseMode) -> Result<ReviewReader, ReadError> {
+        if file_path == STDIN {
+            ReviewReader::from_reader(io::stdin(), mode)
+        } else {
+            ReviewReader::from_reader(File::open(file_path)?, mode)
+        }
+    }
+
+    //Reads a dump from any source, detecting its compression
+    pub fn from_reader<R: Read + 'static>(input: R, mode: ParseMode) -> Result<ReviewReader, ReadError> {
+        Ok(ReviewReader {
+            reader: decode(input)?,
+            mode,
+            report: ReadReport::default(),
+            buf: Vec::new(),
+            done: false,
+        })
+    }
+
+    //What has been read and skipped so far
+    pub fn report(&self) -> &ReadReport {
+        &self.report
+    }
+
+    pub fn into_report(self) -> ReadReport {
+        self.report
+    }
+
+    //Ends the stream, giving the error back when strict or recording it when lenient
+    fn fail(&mut self, error: ReadError) -> Option<ReadError> {
+        self.done = true;
+        match self.mode {
+            ParseMode::Strict => Some(error),
+            ParseMode::Lenient => {
+                self.report.aborted = Some(error);
+                None
+            }
+        }
+    }
+
+    //A malformed line ends the stream when strict and is skipped when lenient
+    fn reject(&mut self, line: usize, source: serde_json::Error) -> Option<ReadError> {
+        let error = ReadError::Json { line, source };
+        match self.mode {
+            ParseMode::Strict => self.fail(error),
+            ParseMode::Lenient => {
+                self.report.skip(error);
+                None
+            }
+        }
+    }
+
+    //Reads the next line that is not blank into buf and returns its number
+    fn next_line(&mut self) -> Option<Result<usize, ReadError>> {
+        while !self.done {
+            self.buf.clear();
+            let line = self.report.lines + 1;
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.report.lines = line;
+                    if !self.buf.iter().all(u8::is_ascii_whitespace) {
+                        return Some(Ok(line));
+                    }
+                }
+                Err(e) => return self.fail(classify_read_error(line, e)).map(Err),
+            }
+        }
+        None
+    }
+
+    //Passes the graph fields of every remaining review to f without collecting the text of the reviews
+    //Malformed lines are handled as by the iterator, a strict read stops at the first one
+    pub fn for_each_edge<F: FnMut(ReviewEdge)>(&mut self, mut f: F) -> Result<(), ReadError> {
+        while let Some(line) = self.next_line() {
+            let line = line?;
+            match serde_json::from_slice::<ReviewEdge>(&self.buf) {
+                Ok(edge) => f(edge),
+                Err(source) => {
+                    if let Some(error) = self.reject(line, source) {
+                        return Err(error);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for ReviewReader {
+    type Item = Result<Review, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(line) = self.next_line() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error)),
+            };
+            match serde_json::from_slice::<Review>(&self.buf) {
+                Ok(review) => return Some(Ok(review)),
+                Err(source) => {
+                    if let Some(error) = self.reject(line, source) {
+                        return Some(Err(error));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+//Read Jsonfile
+//Collects every review of the dump into memory, see ReviewReader to stream them instead
+//In lenient mode only a file that cannot be opened is an error
+pub fn read_jsonfile(file_path: &str, mode: ParseMode) -> Result<(Vec<Review>, ReadReport), ReadError> {
+    let mut reader = ReviewReader::open(file_path, mode)?;
+    let result = reader.by_ref().collect::<Result<Vec<Review>, ReadError>>()?;
+    Ok((result, reader.into_report()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    //Writes the lines to a gzipped file in the temp directory
+    fn write_gz(name: &str, lines: &[&str]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("final_project_{}_{}.json.gz", name, std::process::id()));
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        for line in lines {
+            writeln!(encoder, "{}", line).unwrap();
+        }
+        encoder.finish().unwrap();
+        path
+    }
+
+    const LINES: [&str; 4] = [
+        r#"{"reviewerID": "A1", "asin": "B1"}"#,
+        r#"{"reviewerID": "A2", "asin": "#,
+        "",
+        r#"{"reviewerID": "A3", "asin": "B3"}"#,
+    ];
+
+    #[test]
+    fn test_read_jsonfile_strict() {
+        let path = write_gz("strict", &LINES);
+        let result = read_jsonfile(path.to_str().unwrap(), ParseMode::Strict);
+        std::fs::remove_file(&path).unwrap();
+        match result {
+            Err(ReadError::Json { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a JSON error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_jsonfile_lenient() {
+        let path = write_gz("lenient", &LINES);
+        let (reviews, report) = read_jsonfile(path.to_str().unwrap(), ParseMode::Lenient).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[1].reviewer_id, "A3");
+        assert_eq!(report.lines, 4);
+        assert_eq!(report.skipped, 1);
+        assert!(matches!(report.examples[0], ReadError::Json { line: 2, .. }));
+        assert!(report.aborted.is_none());
+    }
+
+    #[test]
+    fn test_read_jsonfile_truncated() {
+        let path = write_gz("truncated", &LINES);
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 10]).unwrap();
+        let (_, report) = read_jsonfile(path.to_str().unwrap(), ParseMode::Lenient).unwrap();
+        let strict = read_jsonfile(path.to_str().unwrap(), ParseMode::Strict);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(report.aborted, Some(ReadError::Decompress { .. })));
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    fn test_review_reader_stops_after_error() {
+        let path = write_gz("stream", &LINES);
+        let mut reader = ReviewReader::open(path.to_str().unwrap(), ParseMode::Strict).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap().asin, "B1");
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_full_review_record() {
+        let line = r#"{"overall": 5.0, "vote": "1,234", "verified": true, "reviewTime": "09 13, 2009",
+            "reviewerID": "A1", "asin": "B1", "style": {"Format:": " Hardcover"}, "reviewerName": "Ann",
+            "reviewText": "Great", "summary": "Five stars", "unixReviewTime": 1252800000, "image": ["u"]}"#;
+        let review: Review = serde_json::from_str(line).unwrap();
+        assert_eq!(review.reviewer_id, "A1");
+        assert_eq!(review.overall, Some(5.0));
+        assert_eq!(review.vote, Some(1234));
+        assert_eq!(review.verified, Some(true));
+        assert_eq!(review.unix_review_time, Some(1252800000));
+        assert_eq!(review.review_time.as_deref(), Some("09 13, 2009"));
+        assert_eq!(review.style, Some(BTreeMap::from([("Format".to_string(), "Hardcover".to_string())])));
+        assert_eq!(review.image, Some(vec!["u".to_string()]));
+
+        let minimal: Review = serde_json::from_str(r#"{"reviewerID": "A1", "asin": "B1", "vote": null}"#).unwrap();
+        assert_eq!(minimal, Review { reviewer_id: "A1".to_string(), asin: "B1".to_string(), ..Default::default() });
+        let numeric: Review = serde_json::from_str(r#"{"reviewerID": "A1", "asin": "B1", "vote": 7}"#).unwrap();
+        assert_eq!(numeric.vote, Some(7));
+        assert!(serde_json::from_str::<Review>(r#"{"reviewerID": "A1", "asin": "B1", "vote": "lots"}"#).is_err());
+    }
+
+    //Writes raw bytes to a file in the temp directory
+    fn write_bytes(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("final_project_{}_{}", name, std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn read_asins(path: &PathBuf) -> Result<Vec<String>, ReadError> {
+        let result = read_jsonfile(path.to_str().unwrap(), ParseMode::Strict);
+        std::fs::remove_file(path).unwrap();
+        Ok(result?.0.into_iter().map(|r| r.asin).collect())
+    }
+
+    #[test]
+    fn test_read_compressed_and_plain_dumps() {
+        let text = format!("{}\n{}\n", LINES[0], LINES[3]);
+        let expected = vec!["B1".to_string(), "B3".to_string()];
+
+        assert_eq!(read_asins(&write_bytes("plain.json", text.as_bytes())).unwrap(), expected);
+
+        //Two gzip members, as written by pigz or by concatenating .gz files
+        let mut members = Vec::new();
+        for line in [LINES[0], LINES[3]] {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            writeln!(encoder, "{}", line).unwrap();
+            members.extend(encoder.finish().unwrap());
+        }
+        assert_eq!(read_asins(&write_bytes("members.json.gz", &members)).unwrap(), expected);
+
+        let zstd = zstd::stream::encode_all(text.as_bytes(), 3).unwrap();
+        assert_eq!(read_asins(&write_bytes("dump.json.zst", &zstd)).unwrap(), expected);
+
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        let bzip2 = encoder.finish().unwrap();
+        assert_eq!(read_asins(&write_bytes("dump.json.bz2", &bzip2)).unwrap(), expected);
+
+        let truncated = read_asins(&write_bytes("truncated.json.zst", &zstd[..zstd.len() - 4]));
+        assert!(matches!(truncated, Err(ReadError::Decompress { .. })), "{:?}", truncated);
+        assert!(read_asins(&write_bytes("empty.json", b"")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_review_edges() {
+        let line = r#"{"reviewerID": "A1", "asin": "B\u0031", "reviewText": "Great", "summary": "Five stars",
+            "image": ["u"], "overall": 4.0, "vote": "1,234", "verified": true, "unixReviewTime": 1252800000}"#;
+        let edge: ReviewEdge = serde_json::from_str(line).unwrap();
+        let review: Review = serde_json::from_str(line).unwrap();
+        assert_eq!(edge, review.edge());
+        assert!(matches!(edge.reviewer_id, Cow::Borrowed("A1")));
+        assert!(matches!(edge.asin, Cow::Owned(_)));
+
+        let path = write_gz("edges", &LINES);
+        let mut asins = Vec::new();
+        let mut reader = ReviewReader::open(path.to_str().unwrap(), ParseMode::Lenient).unwrap();
+        reader.for_each_edge(|edge| asins.push(edge.asin.into_owned())).unwrap();
+        assert_eq!(asins, vec!["B1", "B3"]);
+        assert_eq!((reader.report().lines, reader.report().skipped), (4, 1));
+
+        let mut reader = ReviewReader::open(path.to_str().unwrap(), ParseMode::Strict).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut edges = 0;
+        let result = reader.for_each_edge(|_| edges += 1);
+        assert!(matches!(result, Err(ReadError::Json { line: 2, .. })));
+        assert_eq!(edges, 1);
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(Codec::detect(&[0x1f, 0x8b, 8, 0]), Codec::Gzip);
+        assert_eq!(Codec::detect(&[0x28, 0xb5, 0x2f, 0xfd]), Codec::Zstd);
+        assert_eq!(Codec::detect(b"BZh9"), Codec::Bzip2);
+        assert_eq!(Codec::detect(b"{\"re"), Codec::Plain);
+        assert_eq!(Codec::detect(b""), Codec::Plain);
+    }
+
+    #[test]
+    fn test_read_jsonfile_missing_file() {
+        let result = read_jsonfile("does_not_exist.json.gz", ParseMode::Lenient);
+        assert!(matches!(result, Err(ReadError::Io(_))));
+    }
+}
@@ -0,0 +1,814 @@
+//Results printed by the command-line subcommands
+//Each report derives Serialize for JSON output and implements Display for text output
+use crate::cli::OutputFormat;
+use final_project::estimate::PathEstimate;
+use final_project::centrality::{Centrality, CentralityScores};
+use final_project::community::{Communities, CommunityMethod};
+use final_project::components::Components;
+use final_project::degrees::DegreeStats;
+use final_project::distances::{DistanceDistribution, PathStats, SIX_DEGREES_SHARE};
+use final_project::graph::{Graph, NodeId, NodeKind, ReviewGraph};
+use final_project::null_model::{NullComparison, NullModelTest};
+use final_project::projection::Normalization;
+use final_project::sample::SampleMethod;
+use final_project::stats::{Summary, WelchTest};
+use final_project::temporal::{Period, Snapshot, SnapshotMode};
+use serde::Serialize;
+use std::fmt;
+
+//A report together with the seed of the random numbers behind it, so the run can be repeated
+#[derive(Serialize, Debug)]
+struct Seeded<'a, T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(flatten)]
+    report: &'a T,
+}
+
+impl<T: fmt::Display> fmt::Display for Seeded<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(seed) = self.seed {
+            writeln!(f, "Seed: {}", seed)?;
+        }
+        write!(f, "{}", self.report)
+    }
+}
+
+//Prints a report in the format chosen on the command line
+//seed is the seed of a random run, None when the run did not draw random numbers
+pub fn emit<T: Serialize + fmt::Display>(format: OutputFormat, seed: Option<u64>, report: &T) {
+    let report = Seeded { seed, report };
+    match format {
+        OutputFormat::Text => print!("{}", report),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Reports always serialize")
+        ),
+    }
+}
+
+//Size of the full review graph
+#[derive(Serialize, Debug)]
+pub struct StatsReport {
+    pub reviews: usize,
+    pub reviewers: usize,
+    pub products: usize,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Reviews: {}", self.reviews)?;
+        writeln!(f, "Reviewers: {}", self.reviewers)?;
+        writeln!(f, "Games: {}", self.products)?;
+        writeln!(f, "Nodes: {}", self.nodes)?;
+        writeln!(f, "Edges: {}", self.edges)
+    }
+}
+
+//Formats an optional distance for text output
+fn distance(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.2}", v),
+        None => "undefined".to_string(),
+    }
+}
+
+//Shortest path statistics of one graph and whether it satisfies six degrees
+//within_six_hops is the share of connected pairs within 6 hops and all_pairs_within_six_hops
+//the share of all ordered pairs, with unreachable pairs counted as misses. Six degrees holds
+//when the share of all pairs reaches SIX_DEGREES_SHARE, see DistanceDistribution::six_degrees
+#[derive(Serialize, Debug)]
+pub struct PathsReport {
+    pub nodes: usize,
+    pub edges: usize,
+    pub components: usize,
+    pub largest_component_size: usize,
+    pub largest_component: bool,
+    pub paths: PathStats,
+    pub diameter: u32,
+    pub effective_diameter: Option<f64>,
+    pub within_six_hops: Option<f64>,
+    pub all_pairs_within_six_hops: Option<f64>,
+    pub six_degrees: bool,
+}
+
+impl PathsReport {
+    pub fn new<G: ReviewGraph>(
+        graph: &G,
+        components: &Components,
+        largest_component: bool,
+        distribution: &DistanceDistribution,
+    ) -> PathsReport {
+        let paths = distribution.path_stats();
+        PathsReport {
+            nodes: graph.node_count(),
+            edges: graph.edge_count(),
+            components: components.count(),
+            largest_component_size: components.largest_size(),
+            largest_component,
+            paths,
+            diameter: distribution.diameter(),
+            effective_diameter: distribution.effective_diameter(),
+            within_six_hops: distribution.share_within(6),
+            all_pairs_within_six_hops: distribution.share_of_pairs_within(6),
+            six_degrees: distribution.six_degrees(),
+        }
+    }
+}
+
+//Formats an optional share as a percentage for text output
+fn percent(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.2}%", 100.0 * v),
+        None => "undefined".to_string(),
+    }
+}
+
+impl fmt::Display for PathsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Nodes: {}, Edges: {}", self.nodes, self.edges)?;
+        writeln!(f, "Components: {}, Largest Component: {} nodes", self.components, self.largest_component_size)?;
+        if self.largest_component {
+            writeln!(f, "Restricted to the largest connected component of {} nodes", self.paths.nodes)?;
+        }
+        writeln!(f, "Average Shortest Path Length = {}", distance(self.paths.average))?;
+        writeln!(f, "Unreachable pairs = {:.2}%", 100.0 * self.paths.unreachable_fraction)?;
+        writeln!(f, "Efficiency = {:.4}, Harmonic Mean Distance = {}", self.paths.efficiency, distance(self.paths.harmonic_distance))?;
+        writeln!(f, "Diameter = {}, Effective Diameter (90%) = {}", self.diameter, distance(self.effective_diameter))?;
+        writeln!(
+            f,
+            "Pairs within 6 hops = {} of all pairs, {} of connected pairs",
+            percent(self.all_pairs_within_six_hops),
+            percent(self.within_six_hops)
+        )?;
+        if self.six_degrees {
+            writeln!(f, "Six degrees of separation hold true: at least {:.0}% of all pairs are within 6 hops.", 100.0 * SIX_DEGREES_SHARE)
+        } else {
+            writeln!(f, "Six degrees of separation do not hold true: fewer than {:.0}% of all pairs are within 6 hops.", 100.0 * SIX_DEGREES_SHARE)
+        }
+    }
+}
+
+//Edges of one sample as reviewer ID and ASIN pairs
+#[derive(Serialize, Debug)]
+pub struct SampleReport {
+    pub edges: Vec<SampleEdge>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SampleEdge {
+    pub reviewer_id: String,
+    pub asin: String,
+}
+
+impl fmt::Display for SampleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for edge in &self.edges {
+            writeln!(f, "{}\t{}", edge.reviewer_id, edge.asin)?;
+        }
+        Ok(())
+    }
+}
+
+//Average shortest paths of several samples
+//shortest holds the index of the sample with the smallest average, or None on a tie
+//or when no sample has a connected pair
+//summary covers the averages of the samples that have one, versus is a second sampling
+//configuration tested against this one
+#[derive(Serialize, Debug)]
+pub struct CompareReport {
+    pub samples: Vec<PathsReport>,
+    pub shortest: Option<usize>,
+    pub summary: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versus: Option<VersusReport>,
+}
+
+//Samples of a second configuration and the tests of whether its average path differs
+#[derive(Serialize, Debug)]
+pub struct VersusReport {
+    pub sampler: SampleMethod,
+    pub sample_size: usize,
+    pub samples: Vec<PathsReport>,
+    pub summary: Summary,
+    pub welch: WelchTest,
+    pub permutations: usize,
+    pub permutation_p_value: f64,
+}
+
+//Mean, variance and interval of the averages of several samples on one line
+fn summary_line(summary: &Summary) -> String {
+    format!(
+        "mean = {:.3}, variance = {:.4}, 95% CI = [{:.3}, {:.3}] over {} samples",
+        summary.mean, summary.variance, summary.ci_low, summary.ci_high, summary.n
+    )
+}
+
+impl fmt::Display for CompareReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, sample) in self.samples.iter().enumerate() {
+            writeln!(f, "Graph {}: Average Shortest Path Length = {}", i + 1, distance(sample.paths.average))?;
+            writeln!(f, "Unreachable pairs in Graph {} = {:.2}%", i + 1, 100.0 * sample.paths.unreachable_fraction)?;
+            writeln!(f, "Graph {} has {} components, the largest with {} of {} nodes", i + 1, sample.components, sample.largest_component_size, sample.nodes)?;
+            writeln!(
+                f,
+                "Pairs within 6 hops in Graph {} = {} of all pairs, {} of connected pairs, Diameter = {}",
+                i + 1,
+                percent(sample.all_pairs_within_six_hops),
+                percent(sample.within_six_hops),
+                sample.diameter
+            )?;
+            if sample.six_degrees {
+                writeln!(f, "Six degrees of separation hold true for Graph {}.", i + 1)?;
+            } else {
+                writeln!(f, "Six degrees of separation do not hold true for Graph {}.", i + 1)?;
+            }
+        }
+        match self.shortest {
+            Some(i) => writeln!(f, "Graph {} has the shortest average shortest path.", i + 1)?,
+            None if self.samples.len() > 1 => writeln!(f, "The graphs have the same average shortest path.")?,
+            None => {}
+        }
+        writeln!(f, "Average Shortest Path Length: {}", summary_line(&self.summary))?;
+        if let Some(versus) = &self.versus {
+            writeln!(
+                f,
+                "Versus {:?} samples of size {}: {}",
+                versus.sampler,
+                versus.sample_size,
+                summary_line(&versus.summary)
+            )?;
+            writeln!(
+                f,
+                "Welch's t = {:.3}, df = {:.1}, p = {:.4}",
+                versus.welch.t, versus.welch.df, versus.welch.p_value
+            )?;
+            writeln!(f, "Permutation test p = {:.4} ({} permutations)", versus.permutation_p_value, versus.permutations)?;
+        }
+        Ok(())
+    }
+}
+
+//Edges of a one-mode projection with the names of both endpoints
+#[derive(Serialize, Debug)]
+pub struct ProjectionReport {
+    pub side: NodeKind,
+    pub normalization: Normalization,
+    pub min_overlap: usize,
+    pub edges: Vec<ProjectionEdge>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProjectionEdge {
+    pub u: String,
+    pub v: String,
+    pub shared: usize,
+    pub weight: f64,
+}
+
+impl fmt::Display for ProjectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for edge in &self.edges {
+            writeln!(f, "{}\t{}\t{}\t{:.4}", edge.u, edge.v, edge.shared, edge.weight)?;
+        }
+        Ok(())
+    }
+}
+
+//Sampled estimate of the average shortest path
+#[derive(Serialize, Debug)]
+pub struct EstimateReport {
+    pub nodes: usize,
+    pub edges: usize,
+    pub estimate: PathEstimate,
+}
+
+impl fmt::Display for EstimateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let e = &self.estimate;
+        writeln!(f, "Nodes: {}, Edges: {}", self.nodes, self.edges)?;
+        writeln!(f, "Estimated Average Shortest Path Length = {:.2} from {} sources", e.mean, e.sources)?;
+        writeln!(f, "Standard error = {:.4}", e.standard_error)?;
+        let method = if e.bootstrap > 0 { "bootstrap" } else { "normal" };
+        writeln!(f, "95% confidence interval ({}) = [{:.2}, {:.2}]", method, e.ci_low, e.ci_high)
+    }
+}
+
+//Connected components of a graph
+//assignments lists the component of every vertex when requested
+#[derive(Serialize, Debug)]
+pub struct ComponentsReport {
+    pub nodes: usize,
+    pub components: usize,
+    pub largest_component_size: usize,
+    pub size_distribution: Vec<SizeCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignments: Option<Vec<ComponentAssignment>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SizeCount {
+    pub size: usize,
+    pub count: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ComponentAssignment {
+    pub kind: NodeKind,
+    pub name: String,
+    pub component: usize,
+}
+
+impl ComponentsReport {
+    pub fn new<G: ReviewGraph>(graph: &G, components: &Components, assignments: bool) -> ComponentsReport {
+        ComponentsReport {
+            nodes: graph.node_count(),
+            components: components.count(),
+            largest_component_size: components.largest_size(),
+            size_distribution: components
+                .size_distribution()
+                .into_iter()
+                .rev()
+                .map(|(size, count)| SizeCount { size, count })
+                .collect(),
+            assignments: assignments.then(|| {
+                graph
+                    .nodes()
+                    .map(|u| ComponentAssignment {
+                        kind: graph.kind(u),
+                        name: graph.name(u).to_string(),
+                        component: components.component_of(u),
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ComponentsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Nodes: {}, Components: {}", self.nodes, self.components)?;
+        let share = if self.nodes == 0 { 0.0 } else { 100.0 * self.largest_component_size as f64 / self.nodes as f64 };
+        writeln!(f, "Largest Component: {} nodes ({:.2}%)", self.largest_component_size, share)?;
+        writeln!(f, "Size\tComponents")?;
+        for entry in &self.size_distribution {
+            writeln!(f, "{}\t{}", entry.size, entry.count)?;
+        }
+        if let Some(assignments) = &self.assignments {
+            writeln!(f, "Kind\tName\tComponent")?;
+            for a in assignments {
+                writeln!(f, "{:?}\t{}\t{}", a.kind, a.name, a.component)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Histogram of shortest path lengths with diameter, radius and eccentricities
+#[derive(Serialize, Debug)]
+pub struct DistancesReport {
+    pub nodes: usize,
+    pub largest_component: bool,
+    pub pairs: u64,
+    pub connected_pairs: u64,
+    pub histogram: Vec<u64>,
+    pub diameter: u32,
+    pub radius: u32,
+    pub effective_diameter: Option<f64>,
+    pub within_six_hops: Option<f64>,
+    pub all_pairs_within_six_hops: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eccentricities: Option<Vec<Eccentricity>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Eccentricity {
+    pub kind: NodeKind,
+    pub name: String,
+    pub eccentricity: u32,
+}
+
+impl DistancesReport {
+    pub fn new<G: ReviewGraph>(
+        graph: &G,
+        distribution: &DistanceDistribution,
+        largest_component: bool,
+        eccentricities: bool,
+    ) -> DistancesReport {
+        DistancesReport {
+            nodes: distribution.nodes,
+            largest_component,
+            pairs: distribution.pairs(),
+            connected_pairs: distribution.connected_pairs(),
+            histogram: distribution.histogram.clone(),
+            diameter: distribution.diameter(),
+            radius: distribution.radius(),
+            effective_diameter: distribution.effective_diameter(),
+            within_six_hops: distribution.share_within(6),
+            all_pairs_within_six_hops: distribution.share_of_pairs_within(6),
+            eccentricities: eccentricities.then(|| {
+                distribution
+                    .sources
+                    .iter()
+                    .zip(&distribution.eccentricities)
+                    .map(|(&u, &eccentricity)| Eccentricity {
+                        kind: graph.kind(u),
+                        name: graph.name(u).to_string(),
+                        eccentricity,
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for DistancesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.largest_component {
+            writeln!(f, "Restricted to the largest connected component of {} nodes", self.nodes)?;
+        }
+        writeln!(f, "Pairs: {}, Connected pairs: {}", self.pairs, self.connected_pairs)?;
+        writeln!(f, "Diameter = {}, Radius = {}, Effective Diameter (90%) = {}", self.diameter, self.radius, distance(self.effective_diameter))?;
+        writeln!(
+            f,
+            "Pairs within 6 hops = {} of all pairs, {} of connected pairs",
+            percent(self.all_pairs_within_six_hops),
+            percent(self.within_six_hops)
+        )?;
+        writeln!(f, "Distance\tPairs\tShare")?;
+        for (d, &count) in self.histogram.iter().enumerate().skip(1) {
+            let share = if self.connected_pairs == 0 { 0.0 } else { count as f64 / self.connected_pairs as f64 };
+            writeln!(f, "{}\t{}\t{:.4}", d, count, share)?;
+        }
+        if let Some(eccentricities) = &self.eccentricities {
+            writeln!(f, "Kind\tName\tEccentricity")?;
+            for e in eccentricities {
+                writeln!(f, "{:?}\t{}\t{}", e.kind, e.name, e.eccentricity)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Shortest chains of reviewers and games between two nodes, empty when they are not connected
+//cost is the total edge cost of a weighted path
+#[derive(Serialize, Debug)]
+pub struct PathReport {
+    pub from: String,
+    pub to: String,
+    pub length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub paths: Vec<Vec<PathStep>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PathStep {
+    pub kind: NodeKind,
+    pub name: String,
+}
+
+impl PathReport {
+    pub fn new(graph: &Graph, from: NodeId, to: NodeId, paths: Vec<Vec<NodeId>>, cost: Option<f64>) -> PathReport {
+        PathReport {
+            from: graph.name(from).to_string(),
+            to: graph.name(to).to_string(),
+            length: paths.first().map(|p| p.len() - 1),
+            cost,
+            paths: paths
+                .into_iter()
+                .map(|p| {
+                    p.into_iter()
+                        .map(|u| PathStep { kind: graph.kind(u), name: graph.name(u).to_string() })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+//Describes one hop of a path in words, e.g. "A reviewed X" or "B also reviewed X"
+fn describe_hop(a: &PathStep, b: &PathStep) -> String {
+    match (a.kind, b.kind) {
+        (NodeKind::Reviewer, NodeKind::Product) => format!("{} reviewed {}", a.name, b.name),
+        (NodeKind::Product, NodeKind::Reviewer) => format!("{} also reviewed {}", b.name, a.name),
+        _ => format!("{} is linked to {}", a.name, b.name),
+    }
+}
+
+impl fmt::Display for PathReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(length) = self.length else {
+            return writeln!(f, "{} and {} are not connected.", self.from, self.to);
+        };
+        match self.cost {
+            Some(cost) => writeln!(f, "{} and {} are {} hops apart at a cost of {:.2}.", self.from, self.to, length, cost)?,
+            None => writeln!(f, "{} and {} are {} hops apart.", self.from, self.to, length)?,
+        }
+        for path in &self.paths {
+            let names: Vec<&str> = path.iter().map(|step| step.name.as_str()).collect();
+            writeln!(f, "{}", names.join(" -> "))?;
+            let hops: Vec<String> = path.windows(2).map(|w| describe_hop(&w[0], &w[1])).collect();
+            if !hops.is_empty() {
+                writeln!(f, "  {}.", hops.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Nodes within a number of hops of a seed set, in BFS order
+#[derive(Serialize, Debug)]
+pub struct NeighborhoodReport {
+    pub hops: u32,
+    pub nodes: Vec<NeighborhoodNode>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NeighborhoodNode {
+    pub kind: NodeKind,
+    pub name: String,
+    pub distance: u32,
+}
+
+impl fmt::Display for NeighborhoodReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} nodes within {} hops", self.nodes.len(), self.hops)?;
+        for node in &self.nodes {
+            writeln!(f, "{:?}\t{}\t{}", node.kind, node.name, node.distance)?;
+        }
+        Ok(())
+    }
+}
+
+//Share of all ordered pairs that lie within a number of hops
+#[derive(Serialize, Debug)]
+pub struct WithinReport {
+    pub hops: u32,
+    pub pairs: u64,
+    pub pairs_within: u64,
+    pub share: Option<f64>,
+}
+
+impl fmt::Display for WithinReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Pairs within {} hops: {} of {} ({})", self.hops, self.pairs_within, self.pairs, percent(self.share))
+    }
+}
+
+//Detected communities with their modularity and the largest ones summarised
+#[derive(Serialize, Debug)]
+pub struct CommunitiesReport {
+    pub method: CommunityMethod,
+    pub communities: usize,
+    pub modularity: f64,
+    pub bipartite_modularity: f64,
+    pub largest: Vec<CommunityReport>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommunityReport {
+    pub community: usize,
+    pub reviewers: usize,
+    pub products: usize,
+    pub top_products: Vec<GameCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameCount {
+    pub asin: String,
+    pub reviews: usize,
+}
+
+impl CommunitiesReport {
+    pub fn new(graph: &Graph, communities: &Communities, limit: usize, top: usize, members: bool) -> CommunitiesReport {
+        let name = |u: NodeId| graph.name(u).to_string();
+        CommunitiesReport {
+            method: communities.method,
+            communities: communities.count(),
+            modularity: communities.modularity,
+            bipartite_modularity: communities.bipartite_modularity,
+            largest: communities
+                .summaries(graph, top)
+                .into_iter()
+                .take(limit)
+                .map(|summary| CommunityReport {
+                    community: summary.community,
+                    reviewers: summary.reviewers.len(),
+                    products: summary.products.len(),
+                    top_products: summary
+                        .top_products
+                        .iter()
+                        .map(|&(game, reviews)| GameCount { asin: name(game), reviews })
+                        .collect(),
+                    members: members.then(|| summary.reviewers.iter().map(|&u| name(u)).collect()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for CommunitiesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Communities: {}", self.communities)?;
+        writeln!(f, "Modularity = {:.4}, Bipartite Modularity = {:.4}", self.modularity, self.bipartite_modularity)?;
+        for c in &self.largest {
+            writeln!(f, "Community {}: {} reviewers, {} games", c.community, c.reviewers, c.products)?;
+            let games: Vec<String> = c.top_products.iter().map(|g| format!("{} ({})", g.asin, g.reviews)).collect();
+            writeln!(f, "  Top games: {}", games.join(", "))?;
+            if let Some(members) = &c.members {
+                writeln!(f, "  Reviewers: {}", members.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Highest scoring reviewers and games under one centrality measure
+#[derive(Serialize, Debug)]
+pub struct CentralityReport {
+    pub measure: Centrality,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<usize>,
+    pub reviewers: Vec<RankedNode>,
+    pub products: Vec<RankedNode>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RankedNode {
+    pub name: String,
+    pub degree: usize,
+    pub score: f64,
+}
+
+impl CentralityReport {
+    pub fn new(graph: &Graph, scores: &CentralityScores, sources: Option<usize>, top: usize) -> CentralityReport {
+        let ranked = |kind| {
+            scores
+                .ranked(graph, kind)
+                .into_iter()
+                .take(top)
+                .map(|(u, score)| RankedNode {
+                    name: graph.name(u).to_string(),
+                    degree: graph.degree(u),
+                    score,
+                })
+                .collect()
+        };
+        CentralityReport {
+            measure: scores.measure,
+            sources: sources.filter(|_| scores.measure == Centrality::Betweenness),
+            reviewers: ranked(NodeKind::Reviewer),
+            products: ranked(NodeKind::Product),
+        }
+    }
+}
+
+impl fmt::Display for CentralityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(sources) = self.sources {
+            writeln!(f, "Estimated from {} sources", sources)?;
+        }
+        for (title, nodes) in [("reviewers", &self.reviewers), ("games", &self.products)] {
+            writeln!(f, "Top {} by {:?}:", title, self.measure)?;
+            for (rank, node) in nodes.iter().enumerate() {
+                writeln!(f, "{:>4}. {} {:.6} (degree {})", rank + 1, node.name, node.score, node.degree)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Degree distributions of both sides of the graph
+#[derive(Serialize, Debug)]
+pub struct DegreesReport {
+    pub reviewers: DegreeStats,
+    pub products: DegreeStats,
+    #[serde(skip)]
+    pub histogram: bool,
+}
+
+impl fmt::Display for DegreesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (title, stats) in [("Reviewers", &self.reviewers), ("Games", &self.products)] {
+            writeln!(f, "{}: {}", title, stats.count)?;
+            writeln!(
+                f,
+                "  Degree mean = {:.2}, median = {}, min = {}, max = {}",
+                stats.mean, stats.median, stats.min, stats.max
+            )?;
+            match &stats.fit {
+                Some(fit) => writeln!(
+                    f,
+                    "  Power law: alpha = {:.3}, xmin = {}, tail = {}, KS = {:.4}",
+                    fit.alpha, fit.xmin, fit.tail, fit.ks
+                )?,
+                None => writeln!(f, "  Power law: too few distinct degrees to fit")?,
+            }
+            if self.histogram {
+                for (degree, vertices) in &stats.histogram {
+                    writeln!(f, "  {:>6}: {}", degree, vertices)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//The graph scored against its degree-preserving null ensemble
+#[derive(Serialize, Debug)]
+pub struct NullModelReport {
+    pub nodes: usize,
+    pub edges: usize,
+    pub largest_component: bool,
+    #[serde(flatten)]
+    pub test: NullModelTest,
+}
+
+//One line of the null model comparison
+fn null_line(f: &mut fmt::Formatter, name: &str, comparison: &NullComparison) -> fmt::Result {
+    writeln!(
+        f,
+        "{}: observed = {:.4}, null = {:.4} +- {:.4}, z = {:.2}",
+        name, comparison.observed, comparison.null_mean, comparison.null_sd, comparison.z_score
+    )
+}
+
+impl fmt::Display for NullModelReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Nodes: {}, Edges: {}", self.nodes, self.edges)?;
+        writeln!(
+            f,
+            "Null ensemble: {} rewired graphs, {} swaps per edge",
+            self.test.models, self.test.swaps_per_edge
+        )?;
+        let paths = if self.largest_component {
+            "Average Shortest Path Length (largest component)"
+        } else {
+            "Average Shortest Path Length"
+        };
+        null_line(f, paths, &self.test.average_shortpath)?;
+        null_line(f, "Bipartite Clustering", &self.test.clustering)?;
+        null_line(f, "Largest Component", &self.test.largest_component)
+    }
+}
+
+//How the graph grew, one snapshot per period
+#[derive(Serialize, Debug)]
+pub struct SnapshotsReport {
+    pub period: Period,
+    pub mode: SnapshotMode,
+    pub window: u64,
+    pub largest_component: bool,
+    pub sources: usize,
+    pub exact_limit: usize,
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl fmt::Display for SnapshotsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let period = match self.period {
+            Period::Year => "year",
+            Period::Quarter => "quarter",
+        };
+        match self.mode {
+            SnapshotMode::Cumulative => writeln!(f, "Cumulative snapshots by {}", period)?,
+            SnapshotMode::Sliding => writeln!(f, "Sliding snapshots over the last {} {}(s)", self.window, period)?,
+        }
+        if self.snapshots.is_empty() {
+            return writeln!(f, "No dated reviews");
+        }
+        if self.largest_component {
+            writeln!(f, "Paths measured inside the largest connected component of each snapshot")?;
+        }
+        if self.snapshots.iter().any(|s| s.estimate.is_some()) {
+            writeln!(f, "Average paths over more than {} vertices are estimated from {} random BFS sources", self.exact_limit, self.sources)?;
+        }
+        writeln!(f, "Period\tReviewers\tGames\tReviews\tDensity\tMean Degree\tComponents\tLargest\tAverage Path\tSources")?;
+        for s in &self.snapshots {
+            let sources = match &s.estimate {
+                Some(estimate) => estimate.sources.to_string(),
+                None => "all".to_string(),
+            };
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{:.6}\t{:.2}\t{}\t{}\t{}\t{}",
+                s.period,
+                s.reviewers,
+                s.products,
+                s.edges,
+                s.density,
+                s.mean_degree,
+                s.components,
+                s.largest_component_size,
+                distance(s.average_path()),
+                sources
+            )?;
+        }
+        Ok(())
+    }
+}
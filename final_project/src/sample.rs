@@ -0,0 +1,484 @@
+//Sampling reviews to reduce the size of the graph
+//Node sampling streams the reviews, the structure-preserving samplers walk a graph already built
+use crate::graph::{Graph, NodeId, NodeKind};
+use crate::reader::{Review, ReviewEdge};
+use clap::ValueEnum;
+use rand::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+//Chance that forest fire sampling burns one more neighbour, giving a mean of p / (1 - p) per vertex
+pub const BURN_PROBABILITY: f64 = 0.7;
+
+//Chance that a random walk jumps back to its start at every step
+pub const RESTART_PROBABILITY: f64 = 0.15;
+
+//Steps a random walk may take without finding a new vertex before it starts over elsewhere
+const MAX_STALL: usize = 1000;
+
+//How a sample of the review graph is drawn
+//Node keeps every review touching the drawn IDs, which gives star-shaped fragments;
+//the other samplers stop once they hold the target number of vertices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SampleMethod {
+    //Uniform reviewer IDs and ASINs with every review touching them
+    Node,
+    //Uniform reviews, keeping only the drawn reviews and their endpoints
+    Edge,
+    //Uniform vertices with only the reviews between them
+    Induced,
+    //Breadth-first search from random seeds
+    Snowball,
+    //Forest fire, burning a geometric number of unburned neighbours of every burning vertex
+    ForestFire,
+    //Random walk with restarts to its start vertex
+    RandomWalk,
+}
+
+//Collects the distinct reviewer IDs and ASINs from a stream of reviews
+//A reviewer and a game with the same ID are different vertices, so every ID keeps its kind
+//The IDs are sorted so a seeded sample_ids draws the same IDs on every run
+pub fn unique_ids<I: IntoIterator<Item = Review>>(reviews: I) -> Vec<(NodeKind, String)> {
+    let mut unique_ids = UniqueIds::default();
+    for review in reviews {
+        unique_ids.insert(&review.edge());
+    }
+    unique_ids.into_sorted()
+}
+
+//The IDs of unique_ids collected one review at a time, for reviews read by ReviewReader::for_each_edge
+//An ID is only copied the first time it is seen
+#[derive(Debug, Default)]
+pub struct UniqueIds {
+    reviewers: BTreeSet<String>,
+    products: BTreeSet<String>,
+}
+
+impl UniqueIds {
+    pub fn insert(&mut self, review: &ReviewEdge) {
+        if !self.reviewers.contains(review.reviewer_id.as_ref()) {
+            self.reviewers.insert(review.reviewer_id.to_string());
+        }
+        if !self.products.contains(review.asin.as_ref()) {
+            self.products.insert(review.asin.to_string());
+        }
+    }
+
+    //Reviewers before games, each sorted by ID, the order sample_ids draws from
+    pub fn into_sorted(self) -> Vec<(NodeKind, String)> {
+        let reviewers = self.reviewers.into_iter().map(|id| (NodeKind::Reviewer, id));
+        let products = self.products.into_iter().map(|id| (NodeKind::Product, id));
+        reviewers.chain(products).collect()
+    }
+}
+
+//Reviewer IDs and ASINs drawn by sample_ids, kept apart like the two sides of the graph
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SampledIds {
+    pub reviewers: HashSet<String>,
+    pub products: HashSet<String>,
+}
+
+impl SampledIds {
+    pub fn len(&self) -> usize {
+        self.reviewers.len() + self.products.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //Whether the review was written by a sampled reviewer or is about a sampled game
+    pub fn touches(&self, review: &ReviewEdge) -> bool {
+        self.reviewers.contains(review.reviewer_id.as_ref()) || self.products.contains(review.asin.as_ref())
+    }
+}
+
+//Randomly samples a subset of reviewer IDs and ASINs
+//Stores the target_size selected IDs in a set for filtering reviews
+pub fn sample_ids<R: Rng>(unique_ids: &[(NodeKind, String)], target_size: usize, rng: &mut R) -> SampledIds {
+    let mut sampled = SampledIds::default();
+    for (kind, id) in unique_ids.choose_multiple(rng, target_size) {
+        match kind {
+            NodeKind::Reviewer => sampled.reviewers.insert(id.clone()),
+            NodeKind::Product => sampled.products.insert(id.clone()),
+        };
+    }
+    sampled
+}
+
+//Keeps the reviews written by or about a sampled ID
+//Reviews are filtered as they stream past so the sample is never stored twice
+pub fn sample_reviews<'a, I>(reviews: I, sample_ids: &'a SampledIds) -> impl Iterator<Item = Review> + 'a
+where
+    I: IntoIterator<Item = Review>,
+    I::IntoIter: 'a,
+{
+    reviews.into_iter().filter(move |r| sample_ids.touches(&r.edge()))
+}
+
+//Unsampled vertices in random order, where the traversal samplers start or start over
+struct Seeds {
+    order: Vec<NodeId>,
+    next: usize,
+}
+
+impl Seeds {
+    fn new<R: Rng>(graph: &Graph, rng: &mut R) -> Seeds {
+        let mut order: Vec<NodeId> = graph.nodes().collect();
+        order.shuffle(rng);
+        Seeds { order, next: 0 }
+    }
+
+    //Next vertex not taken yet, None once every vertex is taken
+    fn take(&mut self, taken: &mut [bool], sampled: &mut Vec<NodeId>) -> Option<NodeId> {
+        while let Some(&u) = self.order.get(self.next) {
+            self.next += 1;
+            if !taken[u as usize] {
+                taken[u as usize] = true;
+                sampled.push(u);
+                return Some(u);
+            }
+        }
+        None
+    }
+}
+
+impl Graph {
+    //Draws a sample of the graph with the given method
+    //target is the number of seed IDs for Node sampling and the number of vertices otherwise
+    pub fn sample<R: Rng>(&self, method: SampleMethod, target: usize, rng: &mut R) -> Graph {
+        match method {
+            SampleMethod::Node => self.sample_nodes(target, rng),
+            SampleMethod::Edge => self.sample_edges(target, rng),
+            SampleMethod::Induced => {
+                let all: Vec<NodeId> = self.nodes().collect();
+                let chosen: Vec<NodeId> = all.choose_multiple(rng, target).copied().collect();
+                self.induced_subgraph(&chosen)
+            }
+            SampleMethod::Snowball => self.induced_subgraph(&self.snowball(target, rng)),
+            SampleMethod::ForestFire => self.induced_subgraph(&self.forest_fire(target, rng)),
+            SampleMethod::RandomWalk => self.induced_subgraph(&self.random_walk(target, rng)),
+        }
+    }
+
+    //Uniform vertices with every edge touching them, like sample_ids and sample_reviews
+    //Vertices are drawn in the order of unique_ids, so a seed picks the same IDs as the streaming sampler
+    fn sample_nodes<R: Rng>(&self, target: usize, rng: &mut R) -> Graph {
+        let mut all: Vec<NodeId> = self.nodes().collect();
+        all.sort_by(|&u, &v| (self.kind(u), self.name(u)).cmp(&(self.kind(v), self.name(v))));
+        let drawn: Vec<NodeId> = all.choose_multiple(rng, target).copied().collect();
+        let mut is_drawn = vec![false; self.node_count()];
+        for &u in &drawn {
+            is_drawn[u as usize] = true;
+        }
+        //An edge between two drawn vertices is only listed by its smaller endpoint,
+        //edge_subgraph would merge a second copy as another review
+        let edges: Vec<(NodeId, NodeId)> = drawn
+            .iter()
+            .flat_map(|&u| self.neighbors(u).iter().map(move |&v| (u, v)))
+            .filter(|&(u, v)| !is_drawn[v as usize] || u < v)
+            .collect();
+        self.edge_subgraph(&edges)
+    }
+
+    //Uniform edges, drawn until their endpoints cover target vertices
+    fn sample_edges<R: Rng>(&self, target: usize, rng: &mut R) -> Graph {
+        let mut edges: Vec<(NodeId, NodeId)> = self
+            .nodes()
+            .flat_map(|u| self.neighbors(u).iter().filter(move |&&v| u < v).map(move |&v| (u, v)))
+            .collect();
+        edges.shuffle(rng);
+        let mut taken = vec![false; self.node_count()];
+        let mut covered = 0;
+        let mut kept = 0;
+        for &(u, v) in &edges {
+            if covered >= target {
+                break;
+            }
+            for w in [u, v] {
+                if !taken[w as usize] {
+                    taken[w as usize] = true;
+                    covered += 1;
+                }
+            }
+            kept += 1;
+        }
+        self.edge_subgraph(&edges[..kept])
+    }
+
+    //Breadth-first search from a random seed, taking every neighbour in turn
+    //A new random seed is drawn whenever a component is exhausted before reaching target
+    fn snowball<R: Rng>(&self, target: usize, rng: &mut R) -> Vec<NodeId> {
+        let mut seeds = Seeds::new(self, rng);
+        let mut taken = vec![false; self.node_count()];
+        let mut sampled = Vec::new();
+        let mut queue = VecDeque::new();
+        while sampled.len() < target {
+            let Some(u) = queue.pop_front().or_else(|| seeds.take(&mut taken, &mut sampled)) else { break };
+            for &v in self.neighbors(u) {
+                if sampled.len() >= target {
+                    break;
+                }
+                if !taken[v as usize] {
+                    taken[v as usize] = true;
+                    sampled.push(v);
+                    queue.push_back(v);
+                }
+            }
+        }
+        sampled
+    }
+
+    //Forest fire sampling (Leskovec and Faloutsos, 2006)
+    //Every burning vertex sets fire to a geometrically distributed number of its unburned
+    //neighbours, and a fire that dies out is restarted at a new random seed
+    fn forest_fire<R: Rng>(&self, target: usize, rng: &mut R) -> Vec<NodeId> {
+        let mut seeds = Seeds::new(self, rng);
+        let mut taken = vec![false; self.node_count()];
+        let mut sampled = Vec::new();
+        let mut queue = VecDeque::new();
+        while sampled.len() < target {
+            let Some(u) = queue.pop_front().or_else(|| seeds.take(&mut taken, &mut sampled)) else { break };
+            let mut burn = 0;
+            while rng.gen_bool(BURN_PROBABILITY) {
+                burn += 1;
+            }
+            let unburned: Vec<NodeId> = self.neighbors(u).iter().copied().filter(|&v| !taken[v as usize]).collect();
+            for &v in unburned.choose_multiple(rng, burn) {
+                if sampled.len() >= target {
+                    break;
+                }
+                taken[v as usize] = true;
+                sampled.push(v);
+                queue.push_back(v);
+            }
+        }
+        sampled
+    }
+
+    //Random walk that returns to its start with probability RESTART_PROBABILITY at every step
+    //The walk starts over at a new random seed when it has found nothing new for MAX_STALL steps
+    fn random_walk<R: Rng>(&self, target: usize, rng: &mut R) -> Vec<NodeId> {
+        let mut seeds = Seeds::new(self, rng);
+        let mut taken = vec![false; self.node_count()];
+        let mut sampled = Vec::new();
+        let mut walk: Option<(NodeId, NodeId)> = None;
+        let mut stall = 0;
+        while sampled.len() < target {
+            let (start, current) = match walk {
+                Some(position) if stall < MAX_STALL => position,
+                _ => {
+                    let Some(seed) = seeds.take(&mut taken, &mut sampled) else { break };
+                    stall = 0;
+                    (seed, seed)
+                }
+            };
+            let next = match self.neighbors(current).choose(rng) {
+                Some(&v) if !rng.gen_bool(RESTART_PROBABILITY) => v,
+                _ => start,
+            };
+            if taken[next as usize] {
+                stall += 1;
+            } else {
+                taken[next as usize] = true;
+                sampled.push(next);
+                stall = 0;
+            }
+            walk = Some((start, next));
+        }
+        sampled
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{NodeKind, ReviewGraph};
+    use rand::rngs::StdRng;
+
+    //Two separate grids of 40 reviewers over a handful of games each
+    fn two_part_graph() -> Graph {
+        let edges: Vec<(String, String)> = (0..80)
+            .flat_map(|i| {
+                let part = i / 40;
+                vec![
+                    (format!("R{}", i), format!("G{}-{}", part, i % 5)),
+                    (format!("R{}", i), format!("H{}-{}", part, i % 7)),
+                ]
+            })
+            .collect();
+        Graph::create_undirected(&edges)
+    }
+
+    //Checks that every edge of the sample is an edge of the original graph
+    fn is_subgraph(sample: &Graph, graph: &Graph) -> bool {
+        sample.reviewers().all(|u| {
+            let original = graph.node_id(NodeKind::Reviewer, sample.name(u)).unwrap();
+            sample
+                .neighbors(u)
+                .iter()
+                .all(|&v| graph.neighbors(original).contains(&graph.node_id(NodeKind::Product, sample.name(v)).unwrap()))
+        })
+    }
+
+    #[test]
+    fn test_samplers_keep_subgraphs_of_target_size() {
+        let graph = two_part_graph();
+        for method in [
+            SampleMethod::Node,
+            SampleMethod::Edge,
+            SampleMethod::Induced,
+            SampleMethod::Snowball,
+            SampleMethod::ForestFire,
+            SampleMethod::RandomWalk,
+        ] {
+            let mut rng = StdRng::seed_from_u64(3);
+            let sample = graph.sample(method, 30, &mut rng);
+            assert!(is_subgraph(&sample, &graph), "{:?}", method);
+            match method {
+                SampleMethod::Node => assert!(sample.node_count() >= 30),
+                SampleMethod::Edge => assert!((30..=31).contains(&sample.node_count())),
+                _ => assert_eq!(sample.node_count(), 30, "{:?}", method),
+            }
+
+            let mut rng = StdRng::seed_from_u64(3);
+            assert_eq!(graph.sample(method, 30, &mut rng).edge_count(), sample.edge_count());
+        }
+    }
+
+    #[test]
+    fn test_traversal_samplers_stay_connected() {
+        //Each half of the graph is connected and has 52 vertices, so 30 fit in one component
+        let graph = two_part_graph();
+        for method in [SampleMethod::Snowball, SampleMethod::RandomWalk] {
+            let mut rng = StdRng::seed_from_u64(5);
+            assert_eq!(graph.sample(method, 30, &mut rng).components().count(), 1, "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn test_samplers_take_whole_graph() {
+        let graph = two_part_graph();
+        let mut rng = StdRng::seed_from_u64(9);
+        for method in [SampleMethod::Snowball, SampleMethod::ForestFire, SampleMethod::RandomWalk] {
+            let sample = graph.sample(method, 1000, &mut rng);
+            assert_eq!(sample.node_count(), graph.node_count());
+            assert_eq!(sample.edge_count(), graph.edge_count());
+        }
+    }
+
+    #[test]
+    fn test_samplers_keep_edge_attributes() {
+        //One review between A and G, drawing both endpoints must not count it twice
+        let review = Review {
+            reviewer_id: "A".to_string(),
+            asin: "G".to_string(),
+            overall: Some(4.0),
+            vote: Some(3),
+            ..Default::default()
+        };
+        let graph = Graph::from_reviews(vec![review]);
+        for method in [SampleMethod::Node, SampleMethod::Edge, SampleMethod::Induced, SampleMethod::Snowball] {
+            let sample = graph.sample(method, 2, &mut StdRng::seed_from_u64(1));
+            let a = sample.node_id(NodeKind::Reviewer, "A").unwrap();
+            let g = sample.node_id(NodeKind::Product, "G").unwrap();
+            let attributes = sample.edge_attributes(a, g).unwrap();
+            assert_eq!(attributes, graph.edges().next().unwrap().2, "{:?}", method);
+            assert_eq!((attributes.reviews, attributes.votes), (1, 3));
+        }
+    }
+
+    #[test]
+    fn test_sample_reviews() {
+        let reviews = vec![
+            Review {
+                reviewer_id: "A1".to_string(),
+                asin: "B1".to_string(),
+                ..Default::default()
+            },
+
+            Review {
+                reviewer_id: "A2".to_string(),
+                asin: "B2".to_string(),
+                ..Default::default()
+            },
+            
+            Review {
+                reviewer_id: "A3".to_string(),
+                asin: "B3".to_string(),
+                ..Default::default()
+            },
+        ];
+        //Two sampled IDs cover one review if they belong to the same review, two otherwise
+        let mut rng = StdRng::seed_from_u64(7);
+        let ids = sample_ids(&unique_ids(reviews.clone()), 2, &mut rng);
+        assert_eq!(ids.len(), 2);
+        let samples: Vec<Review> = sample_reviews(reviews, &ids).collect();
+        assert!(!samples.is_empty() && samples.len() <= 2);
+    }
+
+    #[test]
+    fn test_sampled_ids_keep_their_kind() {
+        //Reviewer X and game X are different vertices, drawing one must not pull in the other
+        let review = |reviewer: &str, asin: &str| Review {
+            reviewer_id: reviewer.to_string(),
+            asin: asin.to_string(),
+            ..Default::default()
+        };
+        let reviews = vec![review("X", "B1"), review("A1", "X")];
+        let ids = unique_ids(reviews.clone());
+        assert_eq!(ids.len(), 4);
+        let reviewer_x = SampledIds { reviewers: HashSet::from(["X".to_string()]), ..Default::default() };
+        let pairs = |ids: &SampledIds| -> Vec<(String, String)> {
+            sample_reviews(reviews.clone(), ids).map(|r| (r.reviewer_id, r.asin)).collect()
+        };
+        assert_eq!(pairs(&reviewer_x), vec![("X".to_string(), "B1".to_string())]);
+        let game_x = SampledIds { products: HashSet::from(["X".to_string()]), ..Default::default() };
+        assert_eq!(pairs(&game_x), vec![("A1".to_string(), "X".to_string())]);
+    }
+
+    #[test]
+    fn test_seeded_sampling_is_reproducible() {
+        let reviews: Vec<Review> = (0..50)
+            .map(|i| Review {
+                reviewer_id: format!("A{}", i),
+                asin: format!("B{}", i % 7),
+                ..Default::default()
+            })
+            .collect();
+        let mut reversed = reviews.clone();
+        reversed.reverse();
+        let ids = unique_ids(reviews);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(unique_ids(reversed), ids);
+
+        let draw = |seed| sample_ids(&ids, 10, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(draw(11), draw(11));
+        assert_ne!(draw(11), draw(12));
+    }
+
+    #[test]
+    fn test_streaming_and_graph_node_samples_agree() {
+        //A seed draws the same IDs whether the reviews are streamed or already in a graph
+        let reviews: Vec<Review> = (0..40)
+            .map(|i| Review {
+                reviewer_id: format!("A{}", i % 11),
+                asin: format!("B{}", i % 7),
+                ..Default::default()
+            })
+            .collect();
+        let graph = Graph::from_reviews(reviews.clone());
+        for seed in 0..5 {
+            let ids = sample_ids(&unique_ids(reviews.clone()), 3, &mut StdRng::seed_from_u64(seed));
+            let streamed = Graph::from_reviews(sample_reviews(reviews.clone(), &ids));
+            let sampled = graph.sample(SampleMethod::Node, 3, &mut StdRng::seed_from_u64(seed));
+            let names = |g: &Graph| -> BTreeSet<(NodeKind, String)> {
+                g.nodes().map(|u| (g.kind(u), g.name(u).to_string())).collect()
+            };
+            assert_eq!(names(&sampled), names(&streamed), "seed {}", seed);
+            assert_eq!(sampled.edge_count(), streamed.edge_count());
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//Small descriptive statistics helpers shared by the estimators and reports
+//plus the Student t distribution for intervals and tests over a handful of samples
+use rand::prelude::*;
+use serde::Serialize;
+
+//Arithmetic mean, NaN for an empty slice
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+//Sample variance with n - 1 in the denominator, NaN for fewer than two values
+pub fn variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return f64::NAN;
+    }
+    let m = mean(values);
+    values.iter().map(|x| (x - m) * (x - m)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+//Standard error of the mean
+pub fn standard_error(values: &[f64]) -> f64 {
+    (variance(values) / values.len() as f64).sqrt()
+}
+
+//Linearly interpolated percentile, p between 0 and 1
+//values must already be sorted, NaN for an empty slice
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let rank = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+//Two-sided critical value of the standard normal distribution for 95% intervals
+pub const Z_95: f64 = 1.959963984540054;
+
+//Natural log of the gamma function, Lanczos approximation with g = 7
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        //Reflection formula
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+//Continued fraction of the incomplete beta function, evaluated with the modified Lentz method
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            result *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    result
+}
+
+//Regularized incomplete beta function I_x(a, b)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+//Cumulative distribution function of Student's t distribution with df degrees of freedom
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+//Quantile of Student's t distribution, found by bisection on the CDF
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    let (mut low, mut high) = (-1e3, 1e3);
+    for _ in 0..200 {
+        let middle = 0.5 * (low + high);
+        if student_t_cdf(middle, df) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    0.5 * (low + high)
+}
+
+//Mean, variance and 95% confidence interval of the mean of a few values
+//The interval uses Student's t with n - 1 degrees of freedom, NaN for fewer than two values
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub n: usize,
+    pub mean: f64,
+    pub variance: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+pub fn summarize(values: &[f64]) -> Summary {
+    let n = values.len();
+    let m = mean(values);
+    let half_width = if n < 2 {
+        f64::NAN
+    } else {
+        student_t_quantile(0.975, (n - 1) as f64) * standard_error(values)
+    };
+    Summary {
+        n,
+        mean: m,
+        variance: variance(values),
+        ci_low: m - half_width,
+        ci_high: m + half_width,
+    }
+}
+
+//Welch's unequal-variance t test of whether two sets of values have the same mean
+#[derive(Debug, Clone, Serialize)]
+pub struct WelchTest {
+    pub t: f64,
+    pub df: f64,
+    pub p_value: f64,
+}
+
+//Two-sided Welch's t test, NaN throughout when either side has fewer than two values
+//or both sides have no spread at all
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> WelchTest {
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let (va, vb) = (variance(a) / na, variance(b) / nb);
+    let t = (mean(a) - mean(b)) / (va + vb).sqrt();
+    let df = (va + vb).powi(2) / (va * va / (na - 1.0) + vb * vb / (nb - 1.0));
+    let p_value = if t.is_finite() && df.is_finite() {
+        2.0 * student_t_cdf(-t.abs(), df)
+    } else {
+        f64::NAN
+    };
+    WelchTest { t, df, p_value }
+}
+
+//Two-sided permutation test of the difference in means
+//The values are pooled and randomly split permutations times, the p-value is the share of
+//splits at least as extreme as the observed one, counting the observed split itself
+pub fn permutation_test<R: Rng>(a: &[f64], b: &[f64], permutations: usize, rng: &mut R) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    let observed = (mean(a) - mean(b)).abs();
+    let mut pooled: Vec<f64> = a.iter().chain(b).copied().collect();
+    let mut extreme = 0;
+    for _ in 0..permutations {
+        pooled.shuffle(rng);
+        let (x, y) = pooled.split_at(a.len());
+        if (mean(x) - mean(y)).abs() >= observed - 1e-12 {
+            extreme += 1;
+        }
+    }
+    (extreme + 1) as f64 / (permutations + 1) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary_statistics() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(mean(&values), 5.0);
+        assert_eq!(variance(&values), 32.0 / 7.0);
+        assert_eq!(standard_error(&values), (32.0 / 7.0 / 8.0_f64).sqrt());
+        assert!(variance(&[1.0]).is_nan());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 0.5), 3.0);
+        assert_eq!(percentile(&sorted, 0.9), 4.6);
+        assert_eq!(percentile(&sorted, 1.0), 5.0);
+        assert!(percentile(&[], 0.5).is_nan());
+    }
+
+    #[test]
+    fn test_student_t() {
+        assert!((student_t_cdf(0.0, 5.0) - 0.5).abs() < 1e-12);
+        assert!((2.0 * student_t_cdf(-2.0, 10.0) - 0.073_388_034_8).abs() < 1e-8);
+        assert!((student_t_quantile(0.975, 10.0) - 2.228_139).abs() < 1e-5);
+        assert!((student_t_quantile(0.975, 1e6) - Z_95).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let summary = summarize(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(summary.n, 8);
+        assert_eq!(summary.mean, 5.0);
+        let half_width = student_t_quantile(0.975, 7.0) * standard_error(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert!((summary.ci_high - 5.0 - half_width).abs() < 1e-12);
+        assert!(summarize(&[3.0]).ci_low.is_nan());
+    }
+
+    #[test]
+    fn test_welch_and_permutation() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+        let welch = welch_t_test(&a, &b);
+        assert_eq!(welch.t, -5.0);
+        assert!((welch.df - 8.0).abs() < 1e-12);
+        assert!((welch.p_value - 0.001_052_826).abs() < 1e-8);
+        assert!(welch_t_test(&[1.0], &b).p_value.is_nan());
+
+        //Only 2 of the 252 splits of ten values into fives are as extreme as the observed one
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let p = permutation_test(&a, &b, 5000, &mut rng);
+        assert!(p < 0.02, "p = {}", p);
+        assert_eq!(permutation_test(&a, &a, 100, &mut rng), 1.0);
+    }
+}
@@ -0,0 +1,274 @@
+//Snapshots of the review graph over time
+//Each snapshot is a filtered view of the reviews written in its time range, so the growth
+//of the reviewer network can be followed one year or quarter at a time
+use crate::dates::{civil_from_timestamp, days_from_civil, SECONDS_PER_DAY};
+use crate::distances::PathStats;
+use crate::estimate::{estimate_from, PathEstimate};
+use crate::filter::{EdgeFilter, GraphView};
+use crate::graph::{Graph, NodeId, ReviewGraph};
+use clap::ValueEnum;
+use rand::Rng;
+use serde::Serialize;
+
+//Largest number of measured vertices whose snapshot paths are searched from every vertex
+pub const EXACT_PATH_LIMIT: usize = 20_000;
+
+//Length of the periods snapshots are taken at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Period {
+    Year,
+    Quarter,
+}
+
+impl Period {
+    //Number of the period a timestamp falls in, counted from year 0
+    pub fn index(self, timestamp: i64) -> i64 {
+        let (year, month, _) = civil_from_timestamp(timestamp);
+        match self {
+            Period::Year => year,
+            Period::Quarter => year * 4 + (month as i64 - 1) / 3,
+        }
+    }
+
+    //Timestamp of midnight UTC on the first day of a period
+    pub fn start(self, index: i64) -> i64 {
+        let days = match self {
+            Period::Year => days_from_civil(index, 1, 1),
+            Period::Quarter => days_from_civil(index.div_euclid(4), index.rem_euclid(4) as u32 * 3 + 1, 1),
+        };
+        days * SECONDS_PER_DAY
+    }
+
+    //Name of a period such as 2014 or 2014-Q2
+    pub fn label(self, index: i64) -> String {
+        match self {
+            Period::Year => index.to_string(),
+            Period::Quarter => format!("{}-Q{}", index.div_euclid(4), index.rem_euclid(4) + 1),
+        }
+    }
+}
+
+//Which reviews a snapshot covers
+//Cumulative snapshots keep every review up to the end of their period,
+//sliding ones only the reviews of the last `window` periods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotMode {
+    Cumulative,
+    Sliding,
+}
+
+//How the average path of a snapshot is measured
+//Exact all-pairs BFS is infeasible on the later snapshots of the full dumps, so a snapshot
+//with more than exact_limit measured vertices estimates it from `sources` random BFS sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathSampling {
+    pub sources: usize,
+    pub exact_limit: usize,
+}
+
+//The graph as it stood in one period
+//Only reviewers and games with a review in the snapshot are its vertices,
+//density is the share of possible reviewer/game pairs that are reviews,
+//and the paths cover those vertices or, when asked, the largest component only.
+//Exactly one of paths and estimate is set, depending on the PathSampling of the snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub period: String,
+    pub since: i64,
+    pub until: i64,
+    pub reviewers: usize,
+    pub products: usize,
+    pub edges: usize,
+    pub density: f64,
+    pub mean_degree: f64,
+    pub components: usize,
+    pub largest_component_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<PathStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<PathEstimate>,
+}
+
+impl Snapshot {
+    //Measures the view of the reviews written in [since, until)
+    fn new<R: Rng>(
+        graph: &GraphView,
+        period: String,
+        since: i64,
+        until: i64,
+        largest_component: bool,
+        sampling: PathSampling,
+        rng: &mut R,
+    ) -> Snapshot {
+        let reviewers = graph.reviewer_count();
+        let products = graph.product_count();
+        let edges = graph.edge_count();
+        let pairs = reviewers * products;
+        let components = graph.components();
+        let measured = if largest_component { components.largest_size() } else { graph.node_count() };
+        let (paths, estimate) = if measured <= sampling.exact_limit {
+            let paths = if largest_component {
+                graph.average_shortpath_largest_component()
+            } else {
+                graph.average_shortpath()
+            };
+            (Some(paths), None)
+        } else {
+            let candidates: Vec<NodeId> = if largest_component { components.members(0) } else { graph.nodes().collect() };
+            (None, Some(estimate_from(graph, &candidates, sampling.sources, 0, rng)))
+        };
+        Snapshot {
+            period,
+            since,
+            until,
+            reviewers,
+            products,
+            edges,
+            density: if pairs == 0 { 0.0 } else { edges as f64 / pairs as f64 },
+            mean_degree: if graph.node_count() == 0 { 0.0 } else { 2.0 * edges as f64 / graph.node_count() as f64 },
+            components: components.count(),
+            largest_component_size: components.largest_size(),
+            paths,
+            estimate,
+        }
+    }
+
+    //Average shortest path between connected pairs, exact or estimated, None when no pair is connected
+    pub fn average_path(&self) -> Option<f64> {
+        match (&self.paths, &self.estimate) {
+            (Some(paths), _) => paths.average,
+            (None, Some(estimate)) => Some(estimate.mean),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Graph {
+    //One snapshot per period from the first to the last dated review
+    //An edge is dated by the earliest of its reviews and undated edges are left out of every snapshot.
+    //window is the number of periods a sliding snapshot covers and is ignored for cumulative ones.
+    //rng draws the BFS sources of the snapshots that sampling estimates
+    pub fn snapshots<R: Rng>(
+        &self,
+        period: Period,
+        mode: SnapshotMode,
+        window: usize,
+        largest_component: bool,
+        sampling: PathSampling,
+        rng: &mut R,
+    ) -> Vec<Snapshot> {
+        let times: Vec<i64> = self.edges().filter_map(|(_, _, attributes)| attributes.time).collect();
+        let (Some(&first), Some(&last)) = (times.iter().min(), times.iter().max()) else {
+            return Vec::new();
+        };
+        let (first, last) = (period.index(first), period.index(last));
+        let window = window.max(1) as i64;
+        (first..=last)
+            .map(|index| {
+                let opening = match mode {
+                    SnapshotMode::Cumulative => first,
+                    SnapshotMode::Sliding => (index + 1 - window).max(first),
+                };
+                let since = period.start(opening);
+                let until = period.start(index + 1);
+                let filter = EdgeFilter { since: Some(since), until: Some(until), ..Default::default() };
+                Snapshot::new(&self.filtered(&filter), period.label(index), since, until, largest_component, sampling, rng)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dates::parse_date;
+    use crate::reader::Review;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const EXACT: PathSampling = PathSampling { sources: 10, exact_limit: EXACT_PATH_LIMIT };
+
+    fn review(reviewer: &str, asin: &str, date: &str) -> Review {
+        Review {
+            reviewer_id: reviewer.to_string(),
+            asin: asin.to_string(),
+            unix_review_time: Some(parse_date(date).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_periods() {
+        let time = parse_date("2014-05-17").unwrap();
+        assert_eq!(Period::Year.index(time), 2014);
+        assert_eq!(Period::Quarter.index(time), 2014 * 4 + 1);
+        assert_eq!(Period::Quarter.label(Period::Quarter.index(time)), "2014-Q2");
+        assert_eq!(Period::Quarter.start(2014 * 4 + 1), parse_date("2014-04-01").unwrap());
+        assert_eq!(Period::Year.start(2015), parse_date("2015-01-01").unwrap());
+        assert_eq!(Period::Year.label(2015), "2015");
+    }
+
+    #[test]
+    fn test_snapshots() {
+        //A and B review G1 in 2012, C joins through G1 in 2014 and nothing happens in 2013
+        let mut reviews = vec![
+            review("A", "G1", "2012-03-01"),
+            review("B", "G1", "2012-11-30"),
+            review("B", "G2", "2012-12-31"),
+            review("C", "G1", "2014-01-01"),
+        ];
+        reviews.push(Review { reviewer_id: "D".to_string(), asin: "G3".to_string(), ..Default::default() });
+        let graph = Graph::from_reviews(reviews);
+
+        let cumulative = graph.snapshots(Period::Year, SnapshotMode::Cumulative, 1, false, EXACT, &mut StdRng::seed_from_u64(1));
+        let periods: Vec<&str> = cumulative.iter().map(|s| s.period.as_str()).collect();
+        assert_eq!(periods, vec!["2012", "2013", "2014"]);
+        assert_eq!(cumulative[0].edges, 3);
+        assert_eq!((cumulative[0].reviewers, cumulative[0].products), (2, 2));
+        assert_eq!(cumulative[0].density, 0.75);
+        assert_eq!(cumulative[1].edges, 3);
+        assert_eq!(cumulative[2].edges, 4);
+        assert_eq!((cumulative[2].components, cumulative[2].largest_component_size), (1, 5));
+        assert_eq!(cumulative[2].paths.as_ref().unwrap().nodes, 5);
+
+        let sliding = graph.snapshots(Period::Year, SnapshotMode::Sliding, 1, false, EXACT, &mut StdRng::seed_from_u64(1));
+        assert_eq!(sliding[0].edges, 3);
+        assert_eq!(sliding[1].edges, 0);
+        assert_eq!(sliding[1].components, 0);
+        assert_eq!(sliding[1].average_path(), None);
+        assert_eq!(sliding[2].edges, 1);
+        assert_eq!(sliding[2].average_path(), Some(1.0));
+
+        let two_years = graph.snapshots(Period::Year, SnapshotMode::Sliding, 2, false, EXACT, &mut StdRng::seed_from_u64(1));
+        assert_eq!(two_years.iter().map(|s| s.edges).collect::<Vec<_>>(), vec![3, 3, 1]);
+
+        let quarters = graph.snapshots(Period::Quarter, SnapshotMode::Cumulative, 1, false, EXACT, &mut StdRng::seed_from_u64(1));
+        assert_eq!(quarters.len(), 9);
+        assert_eq!(quarters[0].period, "2012-Q1");
+        assert_eq!(quarters[3].edges, 3);
+        assert!(Graph::new().snapshots(Period::Year, SnapshotMode::Cumulative, 1, false, EXACT, &mut StdRng::seed_from_u64(1)).is_empty());
+    }
+
+    #[test]
+    fn test_estimated_snapshots() {
+        //A path A-G1-B-G2-C, every vertex as a source gives the exact average back
+        let reviews = vec![
+            review("A", "G1", "2012-03-01"),
+            review("B", "G1", "2012-04-01"),
+            review("B", "G2", "2012-05-01"),
+            review("C", "G2", "2012-06-01"),
+        ];
+        let graph = Graph::from_reviews(reviews);
+        let exact = graph.snapshots(Period::Year, SnapshotMode::Cumulative, 1, false, EXACT, &mut StdRng::seed_from_u64(1));
+        assert!(exact[0].estimate.is_none());
+
+        let sampling = PathSampling { sources: 5, exact_limit: 0 };
+        let estimated = graph.snapshots(Period::Year, SnapshotMode::Cumulative, 1, false, sampling, &mut StdRng::seed_from_u64(1));
+        assert!(estimated[0].paths.is_none());
+        assert_eq!(estimated[0].estimate.as_ref().unwrap().sources, 5);
+        assert_eq!(estimated[0].average_path(), Some(2.0));
+        assert_eq!(estimated[0].average_path(), exact[0].average_path());
+    }
+}
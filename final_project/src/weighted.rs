@@ -0,0 +1,188 @@
+//Weighted shortest paths over the review metadata stored on the edges
+//A cost function turns the attributes of an edge into a non-negative length, so closeness can mean
+//"through games they both loved" rather than just the number of hops
+use crate::graph::{EdgeAttributes, Graph, NodeId};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+//Rating assumed for edges without one, halfway on the 1 to 5 scale
+const NEUTRAL_RATING: f32 = 3.0;
+
+//Lowest rating of a review of a game the reviewer loved
+const LOVED_RATING: f32 = 4.0;
+
+//Ready-made cost functions for the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeCost {
+    //Every edge costs 1, the same paths as the BFS
+    Hops,
+    //6 minus the rating, so a 5-star review costs 1 and a 1-star review 5
+    Rating,
+    //Only reviews rated LOVED_RATING or more can be crossed, each at cost 1
+    Loved,
+    //1 / (1 + helpful votes), so well-received reviews make short edges
+    Helpful,
+}
+
+impl EdgeCost {
+    //Length of an edge, infinity when the edge cannot be used
+    pub fn cost(self, attributes: &EdgeAttributes) -> f64 {
+        let rating = attributes.rating.unwrap_or(NEUTRAL_RATING);
+        match self {
+            EdgeCost::Hops => 1.0,
+            EdgeCost::Rating => 6.0 - rating as f64,
+            EdgeCost::Loved if rating >= LOVED_RATING => 1.0,
+            EdgeCost::Loved => f64::INFINITY,
+            EdgeCost::Helpful => 1.0 / (1.0 + attributes.votes as f64),
+        }
+    }
+}
+
+//Entry of the Dijkstra queue, ordered so the BinaryHeap pops the smallest distance first
+#[derive(PartialEq)]
+struct Candidate {
+    distance: f64,
+    node: NodeId,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.distance.total_cmp(&self.distance).then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Graph {
+    //Dijkstra from start, stopping early once target is settled
+    //Returns the distance and the parent on a cheapest path of every settled vertex
+    fn dijkstra_search<F>(&self, start: NodeId, target: Option<NodeId>, cost: F) -> (Vec<f64>, Vec<NodeId>)
+    where
+        F: Fn(&EdgeAttributes) -> f64,
+    {
+        let n = self.node_count();
+        let mut distances = vec![f64::INFINITY; n];
+        let mut parents: Vec<NodeId> = (0..n as NodeId).collect();
+        let mut queue = BinaryHeap::new();
+        distances[start as usize] = 0.0;
+        queue.push(Candidate { distance: 0.0, node: start });
+        while let Some(Candidate { distance, node }) = queue.pop() {
+            if distance > distances[node as usize] {
+                continue;
+            }
+            if Some(node) == target {
+                break;
+            }
+            for (neighbor, attributes) in self.neighbors_with_attributes(node) {
+                let length = cost(attributes);
+                debug_assert!(length >= 0.0, "edge costs must not be negative");
+                let candidate = distance + length;
+                if candidate < distances[neighbor as usize] {
+                    distances[neighbor as usize] = candidate;
+                    parents[neighbor as usize] = node;
+                    queue.push(Candidate { distance: candidate, node: neighbor });
+                }
+            }
+        }
+        (distances, parents)
+    }
+
+    //Cheapest distance from start to every vertex under the cost function
+    //Costs must not be negative, infinite costs mark edges that cannot be used
+    //and unreachable vertices are at infinity
+    pub fn dijkstra<F>(&self, start: NodeId, cost: F) -> Vec<f64>
+    where
+        F: Fn(&EdgeAttributes) -> f64,
+    {
+        self.dijkstra_search(start, None, cost).0
+    }
+
+    //Cheapest chain of vertices from u to v under the cost function with its total cost,
+    //None if no usable path exists
+    pub fn weighted_shortest_path<F>(&self, u: NodeId, v: NodeId, cost: F) -> Option<(f64, Vec<NodeId>)>
+    where
+        F: Fn(&EdgeAttributes) -> f64,
+    {
+        let (distances, parents) = self.dijkstra_search(u, Some(v), cost);
+        let total = distances[v as usize];
+        if total.is_infinite() {
+            return None;
+        }
+        let mut path = vec![v];
+        let mut current = v;
+        while current != u {
+            current = parents[current as usize];
+            path.push(current);
+        }
+        path.reverse();
+        Some((total, path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::NodeKind;
+    use crate::reader::Review;
+
+    //A and C both reviewed G1 and G2, loving G2 but not G1
+    //G1 is reached first by BFS order, G2 is the path through a game they loved
+    fn rated_graph() -> Graph {
+        let review = |reviewer: &str, asin: &str, overall| Review {
+            reviewer_id: reviewer.to_string(),
+            asin: asin.to_string(),
+            overall: Some(overall),
+            ..Default::default()
+        };
+        Graph::from_reviews(vec![
+            review("A", "G1", 2.0),
+            review("C", "G1", 3.0),
+            review("A", "G2", 5.0),
+            review("C", "G2", 4.0),
+            review("D", "G3", 5.0),
+        ])
+    }
+
+    #[test]
+    fn test_weighted_shortest_path() {
+        let graph = rated_graph();
+        let r = |name| graph.node_id(NodeKind::Reviewer, name).unwrap();
+        let (total, path) = graph.weighted_shortest_path(r("A"), r("C"), |a| EdgeCost::Rating.cost(a)).unwrap();
+        assert_eq!(total, 3.0);
+        assert_eq!(graph.name(path[1]), "G2");
+
+        let (total, path) = graph.weighted_shortest_path(r("A"), r("C"), |a| EdgeCost::Hops.cost(a)).unwrap();
+        assert_eq!((total, path.len()), (2.0, 3));
+        assert!(graph.weighted_shortest_path(r("A"), r("D"), |a| EdgeCost::Hops.cost(a)).is_none());
+        assert_eq!(graph.weighted_shortest_path(r("A"), r("A"), |_| 1.0), Some((0.0, vec![r("A")])));
+    }
+
+    #[test]
+    fn test_dijkstra_skips_unusable_edges() {
+        let graph = rated_graph();
+        let r = |name| graph.node_id(NodeKind::Reviewer, name).unwrap();
+        let g1 = graph.node_id(NodeKind::Product, "G1").unwrap();
+        let distances = graph.dijkstra(r("A"), |a| EdgeCost::Loved.cost(a));
+        assert_eq!(distances[r("C") as usize], 2.0);
+        assert_eq!(distances[g1 as usize], f64::INFINITY);
+
+        //Hop costs give the BFS distances
+        let hops = graph.dijkstra(r("A"), |a| EdgeCost::Hops.cost(a));
+        let bfs = graph.bfs_shortpath(r("A"));
+        for u in graph.nodes() {
+            assert_eq!(hops[u as usize].is_finite(), bfs[u as usize] != crate::graph::UNREACHABLE);
+            if hops[u as usize].is_finite() {
+                assert_eq!(hops[u as usize], bfs[u as usize] as f64);
+            }
+        }
+    }
+}